@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use crate::core::helper;
+use crate::core::Error;
+
+/// The most bytes read from an entry, whatever size its header claims, so
+/// a corrupt archive cannot exhaust the memory.
+const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The archives parsed by earlier reads, kept open so the central
+/// directory of a large archive is not read again for every thumbnail.
+static OPENED: LazyLock<Mutex<HashMap<PathBuf, Opened>>> =
+    LazyLock::new(Mutex::default);
+
+/// The idle parsed copies of an archive, each used by one read at a time.
+/// They are dropped once the file is modified.
+#[derive(Default)]
+struct Opened {
+    modified: Option<SystemTime>,
+    idle: Vec<zip::ZipArchive<fs::File>>,
+}
+
+/// Whether `path` is a zip archive, browsed like a folder of images.
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+        && path.is_file()
+}
+
+/// Lists the images stored in the archive at `path`, each as the path of
+/// the archive joined with the name of its entry. Only entries with one of
+/// the lowercase `extensions` are listed.
+pub fn list_image_files(
+    path: &Path,
+    extensions: &[String],
+) -> Result<Vec<PathBuf>, Error> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let mut paths = Vec::new();
+
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index)?;
+
+        // Names climbing out of the archive are never listed
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+
+        if !entry.is_dir() && helper::is_supported_image(&name, extensions) {
+            paths.push(path.join(name));
+        }
+    }
+
+    Ok(paths)
+}
+
+/// A file stored in a zip archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    pub archive: PathBuf,
+    /// The name of the entry, separated with forward slashes.
+    pub name: String,
+}
+
+impl ArchiveEntry {
+    /// Splits `path` into the archive containing it and the name of its
+    /// entry, if it points inside an archive.
+    pub fn locate(path: &Path) -> Option<Self> {
+        let archive = path.ancestors().skip(1).find(|dir| is_archive(dir))?;
+        let entry = path.strip_prefix(archive).ok()?;
+
+        // Entries are always named with forward slashes
+        let name = entry
+            .components()
+            .map(|component| match component {
+                Component::Normal(part) => Some(part.to_str()?),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?
+            .join("/");
+
+        Some(Self {
+            archive: archive.to_path_buf(),
+            name,
+        })
+    }
+
+    /// Reads the whole entry, inflating it.
+    pub fn read(&self) -> Result<Vec<u8>, Error> {
+        let modified = fs::metadata(&self.archive)?.modified().ok();
+        let mut archive = match take_opened(&self.archive, modified) {
+            Some(archive) => archive,
+            None => zip::ZipArchive::new(fs::File::open(&self.archive)?)?,
+        };
+
+        let bytes = read_entry(&mut archive, &self.name)?;
+
+        let mut opened = OPENED.lock().unwrap_or_else(PoisonError::into_inner);
+        let opened = opened.entry(self.archive.clone()).or_default();
+
+        if opened.modified == modified {
+            opened.idle.push(archive);
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// An idle parsed copy of the archive at `path`, if it was not modified
+/// since it was parsed.
+fn take_opened(
+    path: &Path,
+    modified: Option<SystemTime>,
+) -> Option<zip::ZipArchive<fs::File>> {
+    let mut opened = OPENED.lock().unwrap_or_else(PoisonError::into_inner);
+    let opened = opened.entry(path.to_path_buf()).or_default();
+
+    if opened.modified != modified {
+        opened.modified = modified;
+        opened.idle.clear();
+    }
+
+    opened.idle.pop()
+}
+
+/// Inflates the entry `name`, refusing it once it grows past
+/// [`MAX_ENTRY_SIZE`].
+fn read_entry(
+    archive: &mut zip::ZipArchive<fs::File>,
+    name: &str,
+) -> Result<Vec<u8>, Error> {
+    let entry = archive.by_name(name)?;
+
+    // The size in the header is only a hint, so it is trusted no further
+    // than the limit
+    let capacity = entry.size().min(MAX_ENTRY_SIZE);
+    let mut bytes = Vec::with_capacity(capacity.try_into().unwrap_or(0));
+    entry.take(MAX_ENTRY_SIZE + 1).read_to_end(&mut bytes)?;
+
+    if bytes.len() as u64 > MAX_ENTRY_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the entry is too large",
+        )
+        .into());
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::helper::TempDir;
+
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn archives_list_and_read_their_images() {
+        let dir = TempDir::new("archive");
+        let path = dir.join("photos.zip");
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        for name in ["a.png", "nested/b.jpg", "notes.txt"] {
+            writer.start_file(name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(name.as_bytes()).unwrap();
+        }
+        let _ = writer.finish().unwrap();
+
+        let listed = list_image_files(&path, &helper::default_extensions());
+        let entry = ArchiveEntry::locate(&path.join("nested").join("b.jpg"));
+        let read = entry.as_ref().map(ArchiveEntry::read);
+        let read_again = entry.as_ref().map(ArchiveEntry::read);
+
+        assert_eq!(
+            listed.unwrap(),
+            [path.join("a.png"), path.join("nested/b.jpg")]
+        );
+        assert_eq!(
+            entry,
+            Some(ArchiveEntry {
+                archive: path.clone(),
+                name: "nested/b.jpg".to_owned(),
+            })
+        );
+        assert_eq!(read.unwrap().unwrap(), b"nested/b.jpg");
+        assert_eq!(read_again.unwrap().unwrap(), b"nested/b.jpg");
+        assert_eq!(ArchiveEntry::locate(&dir.join("a.png")), None);
+    }
+}
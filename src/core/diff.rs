@@ -0,0 +1,130 @@
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+use tokio::task;
+
+use std::io;
+
+use crate::core::{Error, Rgba};
+
+/// The per-pixel absolute difference between two images.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// The difference of every channel, opaque, black where the images
+    /// are the same.
+    pub rgba: Rgba,
+    /// How many pixels differ in any channel, transparency included.
+    pub changed: u64,
+}
+
+impl Diff {
+    /// Compares `before` with `after` off the UI thread. An `after` of other
+    /// dimensions is stretched to those of `before` first.
+    pub async fn compute(before: Rgba, after: Rgba) -> Result<Self, Error> {
+        task::spawn_blocking(move || difference(&before, &after)).await?
+    }
+
+    /// Multiplies the difference by `factor` off the UI thread, so small
+    /// changes stand out.
+    pub async fn amplify(self, factor: u8) -> Result<Rgba, Error> {
+        task::spawn_blocking(move || amplify(&self.rgba, factor)).await?
+    }
+}
+
+fn difference(before: &Rgba, after: &Rgba) -> Result<Diff, Error> {
+    let before = buffer(before)?;
+    let mut after = buffer(after)?;
+
+    if after.dimensions() != before.dimensions() {
+        after = imageops::resize(
+            &after,
+            before.width(),
+            before.height(),
+            FilterType::Triangle,
+        );
+    }
+
+    let mut changed = 0;
+
+    let difference =
+        RgbaImage::from_fn(before.width(), before.height(), |x, y| {
+            let [a, b] = [before.get_pixel(x, y), after.get_pixel(x, y)];
+
+            if a != b {
+                changed += 1;
+            }
+
+            image::Rgba([
+                a[0].abs_diff(b[0]),
+                a[1].abs_diff(b[1]),
+                a[2].abs_diff(b[2]),
+                u8::MAX,
+            ])
+        });
+
+    Ok(Diff {
+        rgba: Rgba::from_image_buffer(difference),
+        changed,
+    })
+}
+
+fn amplify(difference: &Rgba, factor: u8) -> Result<Rgba, Error> {
+    let mut amplified = buffer(difference)?;
+
+    for pixel in amplified.pixels_mut() {
+        for channel in &mut pixel.0[..3] {
+            *channel = channel.saturating_mul(factor);
+        }
+    }
+
+    Ok(Rgba::from_image_buffer(amplified))
+}
+
+fn buffer(rgba: &Rgba) -> Result<RgbaImage, Error> {
+    rgba.to_image_buffer().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "malformed bitmap").into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(width: u32, height: u32, pixel: [u8; 4]) -> Rgba {
+        Rgba::from_image_buffer(RgbaImage::from_pixel(
+            width,
+            height,
+            image::Rgba(pixel),
+        ))
+    }
+
+    #[test]
+    fn counts_the_changed_pixels() {
+        let before = filled(4, 4, [10, 20, 30, 255]);
+        let mut after = before.to_image_buffer().unwrap();
+
+        after.put_pixel(1, 2, image::Rgba([14, 20, 25, 255]));
+        after.put_pixel(3, 3, image::Rgba([10, 20, 30, 0]));
+
+        let diff =
+            difference(&before, &Rgba::from_image_buffer(after)).unwrap();
+
+        assert_eq!(diff.changed, 2);
+        assert_eq!(diff.rgba.get_pixel(1, 2), [4, 0, 5, 255]);
+        assert_eq!(diff.rgba.get_pixel(0, 0), [0, 0, 0, 255]);
+
+        let amplified = amplify(&diff.rgba, 100).unwrap();
+
+        assert_eq!(amplified.get_pixel(1, 2), [255, 0, 255, 255]);
+    }
+
+    #[test]
+    fn stretches_images_of_other_dimensions() {
+        let before = filled(4, 4, [0, 0, 0, 255]);
+        let after = filled(2, 2, [0, 0, 0, 255]);
+
+        let diff = difference(&before, &after).unwrap();
+
+        assert_eq!((diff.rgba.width, diff.rgba.height), (4, 4));
+        assert_eq!(diff.changed, 0);
+    }
+}
@@ -0,0 +1,59 @@
+use tokio::task;
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::core::Error;
+
+/// The paths of the images starred by the user.
+pub type Favorites = HashSet<PathBuf>;
+
+/// Loads the favorites saved by a previous session.
+///
+/// Nothing has been starred yet when the file does not exist.
+pub async fn load() -> Result<Favorites, Error> {
+    let favorites = task::spawn_blocking(|| {
+        let Some(path) = path() else {
+            return Ok(Favorites::new());
+        };
+
+        match fs::read(path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Ok(Favorites::new())
+            }
+            Err(error) => Err(error),
+        }
+    })
+    .await??;
+
+    Ok(favorites)
+}
+
+/// Saves the favorites, replacing the ones saved before.
+pub async fn save(favorites: Favorites) -> Result<(), Error> {
+    task::spawn_blocking(move || {
+        let Some(path) = path() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no configuration directory",
+            ));
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(&favorites)?)
+    })
+    .await??;
+
+    Ok(())
+}
+
+fn path() -> Option<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("iced_image_gallery").join("favorites.json"))
+}
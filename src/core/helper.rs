@@ -1,20 +1,370 @@
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+use std::iter::Peekable;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use crate::core::archive;
+use crate::core::remote;
+use crate::core::Error;
+
+/// The extensions of the files listed as images by default, in lowercase.
+pub const EXTENSIONS: &[&str] = &[
+    "jpg",
+    "jpeg",
+    "png",
+    "gif",
+    "svg",
+    #[cfg(feature = "heic")]
+    "heic",
+    #[cfg(feature = "heic")]
+    "heif",
+];
+
+/// The default [`EXTENSIONS`], owned so they can be replaced.
+pub fn default_extensions() -> Vec<String> {
+    EXTENSIONS.iter().map(|extension| extension.to_string()).collect()
+}
+
+/// Whether the extension of the file at `path` is one of the lowercase
+/// `extensions`, ignoring case.
+pub fn is_supported_image(path: &Path, extensions: &[String]) -> bool {
+    let Some(extension) = path.extension().and_then(OsStr::to_str) else {
+        return false;
+    };
+
+    extensions.contains(&extension.to_lowercase())
+}
+
+/// Lists the image files in `dir`, failing when the directory itself
+/// cannot be read. A zip archive lists the images stored inside it, and a
+/// `.urls` file the URLs it holds. Only files with one of the lowercase
+/// `extensions` are listed.
+pub fn list_image_files(
+    dir: &Path,
+    extensions: &[String],
+) -> Result<Vec<PathBuf>, Error> {
+    if archive::is_archive(dir) {
+        return archive::list_image_files(dir, extensions);
+    }
+
+    if remote::is_url_list(dir) {
+        return remote::list_image_urls(dir);
+    }
 
-pub fn list_image_files(dir: &str) -> Vec<PathBuf> {
     let mut paths = Vec::new();
     
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.extension().map_or(false, |ext| {
-                    ["jpg", "jpeg", "png", "gif"].contains(&ext.to_str().unwrap_or("").to_lowercase().as_str())
-                }) {
-                    paths.push(path);
-                }
+    for entry in std::fs::read_dir(dir)? {
+        if let Ok(entry) = entry {
+            let path = entry.path();
+            if is_supported_image(&path, extensions) {
+                paths.push(path);
             }
         }
     }
     
-    paths
-} 
\ No newline at end of file
+    Ok(paths)
+}
+
+/// The image files found by [`scan_image_files`], followed by the
+/// directories that could not be read and why.
+pub type Scanned = (Vec<PathBuf>, Vec<(PathBuf, Error)>);
+
+/// Lists the image files in every directory in the background, setting
+/// aside the directories that cannot be read.
+pub async fn scan_image_files(
+    dirs: Vec<PathBuf>,
+    extensions: Vec<String>,
+) -> Result<Scanned, Error> {
+    let scanned = tokio::task::spawn_blocking(move || {
+        let mut paths = Vec::new();
+        let mut unreadable = Vec::new();
+
+        for dir in dirs {
+            match list_image_files(&dir, &extensions) {
+                Ok(files) => paths.extend(files),
+                Err(error) => unreadable.push((dir, error)),
+            }
+        }
+
+        (paths, unreadable)
+    })
+    .await?;
+
+    Ok(scanned)
+}
+
+/// Opens `url` in the default browser of the platform.
+pub async fn open_url(url: String) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || {
+        let status = open_command(&url).status()?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "the browser exited with {status}"
+            )))
+        }
+    })
+    .await??;
+
+    Ok(())
+}
+
+pub(crate) use desktop::reveal_command;
+use desktop::open_command;
+
+/// Opens URLs in the browser and shows files in the file manager of macOS.
+#[cfg(target_os = "macos")]
+mod desktop {
+    use std::path::Path;
+    use std::process::Command;
+
+    pub fn open_command(url: &str) -> Command {
+        let mut command = Command::new("open");
+        command.arg(url);
+        command
+    }
+
+    pub fn reveal_command(path: &Path) -> Command {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    }
+}
+
+/// Opens URLs in the browser and shows files in the Explorer of Windows.
+#[cfg(target_os = "windows")]
+mod desktop {
+    use std::ffi::OsString;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Hands the URL straight to the default handler, since `cmd` would
+    /// split it at every `&` of its query.
+    pub fn open_command(url: &str) -> Command {
+        let mut command = Command::new("rundll32");
+        command.args(["url.dll,FileProtocolHandler", url]);
+        command
+    }
+
+    pub fn reveal_command(path: &Path) -> Command {
+        let mut select = OsString::from("/select,");
+        select.push(path);
+
+        let mut command = Command::new("explorer");
+        command.arg(select);
+        command
+    }
+}
+
+/// Opens URLs in the browser and shows files in the file manager of the
+/// freedesktop platforms, like Linux and the BSDs.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod desktop {
+    use std::path::Path;
+    use std::process::Command;
+
+    pub fn open_command(url: &str) -> Command {
+        let mut command = Command::new("xdg-open");
+        command.arg(url);
+        command
+    }
+
+    /// Asks the file manager to show the file through the freedesktop
+    /// D-Bus interface, which Nautilus, Dolphin, Thunar and others
+    /// implement.
+    pub fn reveal_command(path: &Path) -> Command {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        let mut uri = String::from("file://");
+        for byte in path.to_string_lossy().bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_'
+                | b'.' | b'~' => uri.push(char::from(byte)),
+                _ => uri.push_str(&format!("%{byte:02X}")),
+            }
+        }
+
+        let mut command = Command::new("dbus-send");
+        command.args([
+            "--session",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+            &format!("array:string:{uri}"),
+            "string:",
+        ]);
+        command
+    }
+}
+
+/// Formats a size in bytes with the largest unit under which it stays at
+/// least 1, like `1.5 MB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Compares file names the way people count, so `img2` comes before
+/// `img10`. Runs of digits compare by their value and letters ignore case.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a_chars);
+                let y = take_number(&mut b_chars);
+
+                // Without leading zeros, longer numbers are larger
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+
+                let _ = a_chars.next();
+                let _ = b_chars.next();
+            }
+        }
+    }
+}
+
+/// Consumes a run of digits, dropping its leading zeros.
+fn take_number(chars: &mut Peekable<Chars<'_>>) -> String {
+    let mut number = String::new();
+
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        if !(number.is_empty() && digit == '0') {
+            number.push(digit);
+        }
+    }
+
+    number
+}
+
+/// A folder of its own for the files of a test, removed with everything in
+/// it once dropped.
+#[cfg(test)]
+pub(crate) struct TempDir(PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    /// Creates the folder, named after `name` and the process so that
+    /// tests and test runs going at once never share one.
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "iced_image_gallery_{name}_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+
+    pub(crate) fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_supported(path: &Path) -> bool {
+        is_supported_image(path, &default_extensions())
+    }
+
+    #[test]
+    fn matches_extensions_ignoring_case() {
+        assert!(is_supported(Path::new("photo.jpg")));
+        assert!(is_supported(Path::new("photo.JPG")));
+        assert!(is_supported(Path::new("dir/Photo.PnG")));
+    }
+
+    #[test]
+    fn rejects_files_without_extension() {
+        assert!(!is_supported(Path::new("photo")));
+        assert!(!is_supported(Path::new("photo.")));
+        assert!(!is_supported(Path::new("")));
+    }
+
+    #[test]
+    fn treats_dotfiles_as_names() {
+        assert!(!is_supported(Path::new(".png")));
+        assert!(is_supported(Path::new(".hidden.png")));
+    }
+
+    #[test]
+    fn formats_sizes_in_the_largest_fitting_unit() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 * 1024 * 1024), "5120.0 GB");
+    }
+
+    #[test]
+    fn matches_only_the_given_extensions() {
+        let raw = ["cr2".to_owned()];
+
+        assert!(is_supported_image(Path::new("photo.CR2"), &raw));
+        assert!(!is_supported_image(Path::new("photo.jpg"), &raw));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn folders_with_names_that_are_not_utf8_are_listed() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp = TempDir::new("not_utf8");
+        let dir = temp.join(OsStr::from_bytes(b"photos\xff"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.png"), []).unwrap();
+
+        let listed = list_image_files(&dir, &default_extensions()).unwrap();
+
+        assert_eq!(listed, [dir.join("a.png")]);
+    }
+
+    #[test]
+    fn matches_only_the_last_extension() {
+        assert!(!is_supported(Path::new("photos.tar.gz")));
+        assert!(!is_supported(Path::new("photo.jpg.bak")));
+        assert!(is_supported(Path::new("backup.tar.png")));
+    }
+}
@@ -0,0 +1,56 @@
+use tokio::task;
+
+use crate::core::{Error, Rgba};
+
+/// The distribution of the red, green, blue and luminance values of an
+/// image, in 256 bins each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub red: [u32; 256],
+    pub green: [u32; 256],
+    pub blue: [u32; 256],
+    pub luminance: [u32; 256],
+}
+
+impl Histogram {
+    /// Counts the values of every pixel, off the UI thread.
+    pub async fn compute(rgba: Rgba) -> Result<Self, Error> {
+        let histogram = task::spawn_blocking(move || {
+            let mut histogram = Self {
+                red: [0; 256],
+                green: [0; 256],
+                blue: [0; 256],
+                luminance: [0; 256],
+            };
+
+            for pixel in rgba.pixels.chunks_exact(4) {
+                let [red, green, blue] = [pixel[0], pixel[1], pixel[2]];
+
+                // Rec. 709 weights, as perceived by the eye
+                let luminance = 0.2126 * f32::from(red)
+                    + 0.7152 * f32::from(green)
+                    + 0.0722 * f32::from(blue);
+
+                histogram.red[usize::from(red)] += 1;
+                histogram.green[usize::from(green)] += 1;
+                histogram.blue[usize::from(blue)] += 1;
+                histogram.luminance[luminance.round() as usize] += 1;
+            }
+
+            histogram
+        })
+        .await?;
+
+        Ok(histogram)
+    }
+
+    /// The largest count of any bin, used to scale the chart.
+    pub fn peak(&self) -> u32 {
+        [&self.red, &self.green, &self.blue, &self.luminance]
+            .into_iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+}
@@ -1,56 +1,929 @@
 use bytes::Bytes;
+use iced::futures::{Stream, StreamExt, stream};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{
+    DynamicImage, GrayImage, ImageDecoder, ImageFormat, ImageReader, RgbImage,
+    RgbaImage, imageops,
+};
+use tokio::sync::Semaphore;
 use tokio::task;
+use tokio::time;
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsStr;
 use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{self, AtomicU32};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::thread;
+use std::time::Duration;
 
+use crate::core::archive::ArchiveEntry;
+use crate::core::remote::RemoteImage;
 use crate::core::helper;
+use crate::core::orientation;
+#[cfg(feature = "metrics")]
+use crate::core::metrics;
+use crate::core::Metadata;
 
 #[derive(Debug, Clone)]
 pub struct ImageData {
     pub id: Id,
     path: PathBuf,
+    source: Source,
+    is_incomplete: bool,
+    /// The width and height as displayed, read from the header when listed.
+    dimensions: Option<(u32, u32)>,
 }
 
 impl ImageData {
+    /// The amount of images on each page of the gallery.
     pub const LIMIT: usize = 1000;
+    pub const BATCH_SIZE: usize = 100;
 
-    pub async fn list_from_paths(paths: Vec<PathBuf>) -> Result<Vec<Self>, Error> {
-        let mut images = Vec::new();
-        
-        for (id, path) in paths.into_iter().enumerate() {
-            images.push(ImageData {
-                id: Id(id as u32),
-                path,
-            });
-        }
+    pub async fn list_from_paths(
+        paths: Vec<PathBuf>,
+        ids: Ids,
+    ) -> Result<Vec<Self>, Error> {
+        let images = task::spawn_blocking(move || {
+            paths
+                .into_iter()
+                .map(|path| {
+                    if let Some(remote) = RemoteImage::locate(&path) {
+                        return ImageData {
+                            id: ids.of(&path),
+                            path,
+                            source: Source::Remote(remote),
+                            is_incomplete: false,
+                            dimensions: None,
+                        };
+                    }
+
+                    if let Some(entry) = ArchiveEntry::locate(&path) {
+                        // Reading the header means inflating the whole
+                        // entry, so its dimensions are left unknown
+                        return ImageData {
+                            id: ids.of(&path),
+                            path,
+                            source: Source::ArchiveEntry(entry),
+                            is_incomplete: false,
+                            dimensions: None,
+                        };
+                    }
+
+                    // Placeholders of files that are still syncing are empty
+                    let is_incomplete = fs::metadata(&path)
+                        .is_ok_and(|metadata| metadata.len() == 0);
+
+                    let dimensions = if is_incomplete {
+                        None
+                    } else {
+                        oriented_dimensions(&path).ok()
+                    };
+
+                    ImageData {
+                        id: ids.of(&path),
+                        source: Source::File,
+                        path,
+                        is_incomplete,
+                        dimensions,
+                    }
+                })
+                .collect()
+        })
+        .await?;
 
         Ok(images)
     }
 
-    pub async fn download(self, size: Size) -> Result<Rgba, Error> {
-        let image = task::spawn_blocking(move || {
-            Ok::<_, Error>(
-                image::open(&self.path)?
-                    .to_rgba8(),
-            )
+    /// Creates an image for `path` without touching the file system.
+    #[cfg(test)]
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+
+        Self {
+            id: Id::unique(),
+            source: Source::File,
+            path,
+            is_incomplete: false,
+            dimensions: None,
+        }
+    }
+
+    /// Gives the image `dimensions`, as if read from its header.
+    #[cfg(test)]
+    pub fn with_dimensions(self, dimensions: (u32, u32)) -> Self {
+        Self {
+            dimensions: Some(dimensions),
+            ..self
+        }
+    }
+
+    /// Lists the images at `paths` in batches of [`Self::BATCH_SIZE`],
+    /// preserving their order and taking their [`Id`]s from `ids`.
+    pub fn list_in_batches(
+        paths: Vec<PathBuf>,
+        ids: Ids,
+    ) -> impl Stream<Item = Result<Vec<Self>, Error>> {
+        let batches: Vec<Vec<PathBuf>> = paths
+            .chunks(Self::BATCH_SIZE)
+            .map(<[PathBuf]>::to_vec)
+            .collect();
+
+        stream::iter(batches)
+            .then(move |batch| Self::list_from_paths(batch, ids.clone()))
+    }
+
+    /// The path of the file, or of the archive joined with the name of the
+    /// entry for images stored in an archive.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// The file of the image, unless it is stored in an archive or
+    /// downloaded.
+    pub fn file(&self) -> Option<&Path> {
+        match self.source {
+            Source::File => Some(&self.path),
+            Source::ArchiveEntry(_) | Source::Remote(_) => None,
+        }
+    }
+
+    /// Whether the file was empty when it was listed, like a placeholder
+    /// of a file that has not finished syncing.
+    pub fn is_incomplete(&self) -> bool {
+        self.is_incomplete
+    }
+
+    /// The width and height of the image as it is displayed, if its header
+    /// could be read.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        self.dimensions
+    }
+
+    /// Points the image at the new location of its file after a rename.
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Reads the dimensions from the header of the image and the size of its
+    /// file, without decoding any pixels.
+    pub async fn details(self) -> Result<Details, Error> {
+        task::spawn_blocking(move || {
+            let caption = self.file().and_then(caption);
+
+            let (bytes, (width, height)) = match self.file() {
+                Some(path) => (fs::metadata(path)?.len(), dimensions(path)?),
+                None => {
+                    let bytes = self.read()?;
+                    let dimensions = ImageReader::new(io::Cursor::new(&bytes))
+                        .with_guessed_format()?
+                        .into_dimensions()?;
+
+                    (bytes.len() as u64, dimensions)
+                }
+            };
+
+            Ok::<_, Error>(Details {
+                width,
+                height,
+                bytes,
+                caption,
+            })
+        })
+        .await?
+    }
+
+    pub async fn metadata(self) -> Result<Metadata, Error> {
+        let metadata = task::spawn_blocking(move || match self.file() {
+            Some(path) => Ok(Metadata {
+                caption: caption(path),
+                ..Metadata::read(path)?
+            }),
+            None => {
+                let bytes = self.read()?;
+
+                Ok::<_, Error>(Metadata::from_reader(&mut io::Cursor::new(
+                    bytes,
+                )))
+            }
         })
         .await??;
-        Ok(Rgba {
-            width: image.width(),
-            height: image.height(),
-            pixels: Bytes::from(image.into_raw()),
+
+        Ok(metadata)
+    }
+
+    /// Copies the file into `dir`, refusing to overwrite an existing file.
+    pub async fn copy_to(self, dir: PathBuf) -> Result<(), Error> {
+        task::spawn_blocking(move || {
+            let target = dir.join(self.path.file_name().unwrap_or_default());
+
+            if target.exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", target.display()),
+                )
+                .into());
+            }
+
+            match self.file() {
+                Some(path) => fs::copy(path, target).map(|_| ())?,
+                None => fs::write(target, self.read()?)?,
+            }
+
+            Ok::<_, Error>(())
         })
+        .await??;
+
+        Ok(())
+    }
+
+    /// Places the absolute path of the file on the system clipboard as text.
+    pub async fn copy_path_to_clipboard(self) -> Result<(), Error> {
+        task::spawn_blocking(move || {
+            let path = match &self.source {
+                Source::File => self.path.canonicalize()?,
+                Source::ArchiveEntry(entry) => {
+                    entry.archive.canonicalize()?.join(&entry.name)
+                }
+                Source::Remote(remote) => PathBuf::from(&remote.url),
+            };
+
+            let mut clipboard =
+                CLIPBOARD.lock().unwrap_or_else(PoisonError::into_inner);
+
+            let mut current = match clipboard.take() {
+                Some(current) => current,
+                None => arboard::Clipboard::new()?,
+            };
+
+            let result = current.set_text(path.to_string_lossy());
+
+            *clipboard = Some(current);
+            Ok::<_, Error>(result?)
+        })
+        .await?
+    }
+
+    /// Whether the file can be rotated without losing anything: JPEGs are
+    /// rotated through their EXIF orientation and PNGs are lossless.
+    ///
+    /// Other formats would lose their animation, their vectors or their
+    /// quality when encoded again.
+    pub fn can_rotate(&self) -> bool {
+        self.file().is_some_and(|path| is_jpeg(path) || is_png(path))
+    }
+
+    /// Rotates the original file clockwise by the given amount of quarter
+    /// turns. JPEGs only have their EXIF orientation changed, while PNGs
+    /// are encoded again.
+    pub async fn rotate(self, quarter_turns: u8) -> Result<(), Error> {
+        task::spawn_blocking(move || {
+            let Some(path) = self.file() else {
+                return Err(read_only().into());
+            };
+
+            if !self.can_rotate() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "only JPEG and PNG files can be rotated",
+                )
+                .into());
+            }
+
+            if is_jpeg(path) {
+                return Ok(orientation::rotate_jpeg(path, quarter_turns)?);
+            }
+
+            let image = decode(path)?;
+
+            let rotated = match quarter_turns % 4 {
+                1 => image.rotate90(),
+                2 => image.rotate180(),
+                3 => image.rotate270(),
+                _ => return Ok(()),
+            };
+
+            rotated.save_with_format(path, ImageFormat::Png)?;
+
+            Ok::<_, Error>(())
+        })
+        .await?
+    }
+
+    /// Saves a copy of the image at its native resolution as `format`,
+    /// turned clockwise by `quarter_turns` like the viewer shows it.
+    pub async fn export(
+        self,
+        path: PathBuf,
+        format: ExportFormat,
+        quarter_turns: u8,
+    ) -> Result<(), Error> {
+        let rgba = self.download(Size::Actual).await?;
+
+        task::spawn_blocking(move || {
+            rgba.turn(quarter_turns).write(&path, format)
+        })
+        .await?
+    }
+
+    /// Moves the file to the trash of the operating system.
+    pub async fn trash(self) -> Result<(), Error> {
+        task::spawn_blocking(move || match self.file() {
+            Some(path) => Ok(trash::delete(path)?),
+            None => Err(read_only().into()),
+        })
+        .await?
+    }
+
+    /// Opens the folder of the file in the file manager of the platform,
+    /// selecting the file when the file manager supports it. Images stored
+    /// in an archive reveal the archive, and remote ones open in the browser.
+    pub async fn reveal(self) -> Result<(), Error> {
+        let path = match self.source {
+            Source::File => self.path,
+            Source::ArchiveEntry(entry) => entry.archive,
+            Source::Remote(remote) => return helper::open_url(remote.url).await,
+        };
+
+        task::spawn_blocking(move || {
+
+            let status = helper::reveal_command(&path).status()?;
+
+            if status.success() {
+                Ok(())
+            } else {
+                Err(io::Error::other(format!(
+                    "the file manager exited with {status}"
+                )))
+            }
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    /// Limits how many thumbnails may be decoded at the same time.
+    ///
+    /// Defaults to the number of CPU cores and only takes effect before the
+    /// first thumbnail is downloaded.
+    pub fn set_thumbnail_decoders(limit: usize) {
+        let _ = THUMBNAIL_DECODERS.set(Semaphore::new(limit.max(1)));
+    }
+
+    pub async fn download(self, size: Size) -> Result<Rgba, Error> {
+        // Originals are never throttled, so the viewer stays responsive
+        let permit = match size {
+            Size::Thumbnail { .. } => thumbnail_decoders().acquire().await.ok(),
+            Size::Original | Size::Actual => None,
+        };
+
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        let decoding = task::spawn_blocking(move || {
+            // Held until the decode ends, even once it is no longer waited
+            // for, so a stuck decode still counts against the limit
+            let _permit = permit;
+
+            self.decode(size)
+        });
+
+        // A malformed file may keep the decoder busy indefinitely. The
+        // blocking task cannot be cancelled, but it is not waited for.
+        // Inspecting a huge image at 100% may take long on its own, so it
+        // is waited for however long it takes
+        let image = match size {
+            Size::Actual => decoding.await??,
+            Size::Original | Size::Thumbnail { .. } => {
+                time::timeout(DECODE_TIMEOUT, decoding).await???
+            }
+        };
+        let rgba = Rgba::from(image);
+
+        #[cfg(feature = "metrics")]
+        metrics::record(size, started.elapsed());
+
+        Ok(rgba)
+    }
+
+    /// Reads the encoded bytes of the image, blocking until they are read.
+    fn read(&self) -> Result<Vec<u8>, Error> {
+        match &self.source {
+            Source::File => Ok(fs::read(&self.path)?),
+            Source::ArchiveEntry(entry) => entry.read(),
+            Source::Remote(remote) => remote.read(),
+        }
+    }
+
+    /// Decodes the image into RGBA pixels of the requested size, blocking
+    /// until it is decoded.
+    fn decode(&self, size: Size) -> Result<RgbaImage, Error> {
+        match &self.source {
+            Source::File => load(&self.path, size),
+            Source::ArchiveEntry(_) | Source::Remote(_) => {
+                load_bytes(self.read()?, size)
+            }
+        }
+    }
+}
+
+/// Images are equal when they are the same file, whichever path it was
+/// listed through.
+impl PartialEq for ImageData {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
     }
 }
 
+impl Eq for ImageData {}
+
+impl Hash for ImageData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// The extensions of the sidecar files describing an image, tried in order.
+const CAPTION_EXTENSIONS: &[&str] = &["txt", "caption"];
+
+/// Reads the description of the image at `path` from a sibling file of the
+/// same name, like `photo.txt` next to `photo.jpg`.
+///
+/// Missing, unreadable and empty sidecars are all no caption.
+fn caption(path: &Path) -> Option<String> {
+    CAPTION_EXTENSIONS.iter().find_map(|extension| {
+        let caption = fs::read_to_string(path.with_extension(extension)).ok()?;
+        let caption = caption.trim();
+
+        (!caption.is_empty()).then(|| caption.to_owned())
+    })
+}
+
+/// The error of changing an image that is not a file on disk.
+fn read_only() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "only images stored as files can be changed",
+    )
+}
+
+/// How long an image may take to decode before it is reported as failed.
+const DECODE_TIMEOUT: Duration = Duration::from_secs(20);
+
+static THUMBNAIL_DECODERS: OnceLock<Semaphore> = OnceLock::new();
+
+fn thumbnail_decoders() -> &'static Semaphore {
+    THUMBNAIL_DECODERS.get_or_init(|| {
+        let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+
+        Semaphore::new(cores)
+    })
+}
+
+/// Decodes the file at `path` into RGBA pixels of the requested size.
+fn load(path: &Path, size: Size) -> Result<RgbaImage, Error> {
+    if is_svg(path) {
+        return rasterize(path, size);
+    }
+
+    // Huge originals are capped before decoding when possible
+    let bounds = match size {
+        Size::Thumbnail { width, height, .. } => Some((width, height)),
+        Size::Original => dimensions(path)
+            .ok()
+            .and_then(|(width, height)| capped(width, height)),
+        Size::Actual => None,
+    };
+
+    let image = match bounds {
+        Some((width, height)) if is_jpeg(path) => {
+            match decode_scaled_jpeg(path, width, height) {
+                Some(image) => image,
+                None => decode(path)?,
+            }
+        }
+        _ => decode(path)?,
+    };
+
+    let bounds = match size {
+        Size::Original => capped(image.width(), image.height()),
+        _ => bounds,
+    };
+
+    Ok(fit(image, bounds, size.filter()))
+}
+
+/// Decodes the encoded `bytes` of an image into RGBA pixels of the
+/// requested size.
+fn load_bytes(bytes: Vec<u8>, size: Size) -> Result<RgbaImage, Error> {
+    let mut decoder = ImageReader::new(io::Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_decoder()?;
+
+    let orientation = decoder.orientation()?;
+
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    let bounds = match size {
+        Size::Thumbnail { width, height, .. } => Some((width, height)),
+        Size::Original => capped(image.width(), image.height()),
+        Size::Actual => None,
+    };
+
+    Ok(fit(image, bounds, size.filter()))
+}
+
+/// Converts `image` to RGBA, keeping its alpha channel, and scales it down
+/// with `filter` to fit within `bounds`, preserving its aspect ratio.
+fn fit(
+    image: DynamicImage,
+    bounds: Option<(u32, u32)>,
+    filter: ResizeFilter,
+) -> RgbaImage {
+    match bounds {
+        Some((width, height))
+            if image.width() > width || image.height() > height =>
+        {
+            image.resize(width, height, filter.filter_type()).to_rgba8()
+        }
+        _ => image.to_rgba8(),
+    }
+}
+
+/// The most pixels an original is decoded with, as many as an 8K display
+/// shows. Larger originals take hundreds of megabytes once decoded.
+pub const MAX_ORIGINAL_PIXELS: u64 = 7680 * 4320;
+
+/// The dimensions an image of `width` × `height` is scaled down to so it
+/// stays within [`MAX_ORIGINAL_PIXELS`], if it is larger.
+fn capped(width: u32, height: u32) -> Option<(u32, u32)> {
+    let pixels = u64::from(width) * u64::from(height);
+
+    if pixels <= MAX_ORIGINAL_PIXELS {
+        return None;
+    }
+
+    let scale = (MAX_ORIGINAL_PIXELS as f64 / pixels as f64).sqrt();
+
+    Some((
+        ((f64::from(width) * scale) as u32).max(1),
+        ((f64::from(height) * scale) as u32).max(1),
+    ))
+}
+
+/// Reads the dimensions of the image at `path` from its header.
+fn dimensions(path: &Path) -> Result<(u32, u32), Error> {
+    if is_svg(path) {
+        let tree = svg_tree(path)?;
+
+        return Ok((
+            tree.size().width().round() as u32,
+            tree.size().height().round() as u32,
+        ));
+    }
+
+    #[cfg(feature = "heic")]
+    if is_heif(path) {
+        let context =
+            libheif_rs::HeifContext::read_from_file(&path.to_string_lossy())?;
+        let handle = context.primary_image_handle()?;
+
+        return Ok((handle.width(), handle.height()));
+    }
+
+    Ok(ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_dimensions()?)
+}
+
+/// Reads the dimensions of the image at `path` from its header, swapping
+/// them when its EXIF orientation turns it sideways.
+fn oriented_dimensions(path: &Path) -> Result<(u32, u32), Error> {
+    use image::metadata::Orientation;
+
+    if is_svg(path) {
+        return dimensions(path);
+    }
+
+    #[cfg(feature = "heic")]
+    if is_heif(path) {
+        return dimensions(path);
+    }
+
+    let mut decoder = ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_decoder()?;
+
+    let (width, height) = decoder.dimensions();
+
+    Ok(match decoder.orientation()? {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => (height, width),
+        _ => (width, height),
+    })
+}
+
+/// Decodes the image at `path`, applying its EXIF orientation so photos
+/// taken in portrait are displayed upright.
+fn decode(path: &Path) -> Result<DynamicImage, Error> {
+    #[cfg(feature = "heic")]
+    if is_heif(path) {
+        return decode_heif(path);
+    }
+
+    let mut decoder = ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_decoder()?;
+
+    let orientation = decoder.orientation()?;
+
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    Ok(image)
+}
+
+/// Decodes a JPEG at a fraction of its resolution through DCT scaling,
+/// still covering `width` × `height` on at least one axis.
+///
+/// Returns `None` when the JPEG needs the regular decoder instead, like
+/// CMYK or 16-bit images.
+fn decode_scaled_jpeg(
+    path: &Path,
+    width: u32,
+    height: u32,
+) -> Option<DynamicImage> {
+    use jpeg_decoder::{Decoder, PixelFormat};
+
+    let clamp = |value: u32| value.min(u32::from(u16::MAX)) as u16;
+
+    let file = fs::File::open(path).ok()?;
+    let mut decoder = Decoder::new(io::BufReader::new(file));
+
+    let (width, height) = decoder.scale(clamp(width), clamp(height)).ok()?;
+    let pixels = decoder.decode().ok()?;
+    let (width, height) = (u32::from(width), u32::from(height));
+
+    let mut image = match decoder.info()?.pixel_format {
+        PixelFormat::RGB24 => {
+            DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, pixels)?)
+        }
+        PixelFormat::L8 => {
+            DynamicImage::ImageLuma8(GrayImage::from_raw(width, height, pixels)?)
+        }
+        PixelFormat::L16 | PixelFormat::CMYK32 => return None,
+    };
+
+    let orientation = ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()?
+        .orientation()
+        .ok()?;
+
+    image.apply_orientation(orientation);
+
+    Some(image)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extensions
+                .iter()
+                .any(|candidate| extension.eq_ignore_ascii_case(candidate))
+        })
+}
+
+fn is_jpeg(path: &Path) -> bool {
+    has_extension(path, &["jpg", "jpeg"])
+}
+
+fn is_png(path: &Path) -> bool {
+    has_extension(path, &["png"])
+}
+
+fn is_svg(path: &Path) -> bool {
+    has_extension(path, &["svg"])
+}
+
+/// Vector images have no native resolution, so their originals are rendered
+/// with the longer side at this many pixels, sharp in any common window.
+const SVG_RESOLUTION: u32 = 2048;
+
+fn svg_tree(path: &Path) -> Result<resvg::usvg::Tree, Error> {
+    use resvg::usvg;
+
+    // Loading the system fonts is slow, so it only happens once
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+
+    let fontdb = FONTS
+        .get_or_init(|| {
+            let mut fonts = usvg::fontdb::Database::new();
+            fonts.load_system_fonts();
+
+            Arc::new(fonts)
+        })
+        .clone();
+
+    let options = usvg::Options {
+        resources_dir: path.parent().map(Path::to_path_buf),
+        fontdb,
+        ..usvg::Options::default()
+    };
+
+    Ok(usvg::Tree::from_data(&fs::read(path)?, &options)?)
+}
+
+/// Rasterizes the SVG at `path` to fit the requested size, preserving its
+/// aspect ratio.
+fn rasterize(path: &Path, size: Size) -> Result<RgbaImage, Error> {
+    use resvg::tiny_skia;
+
+    let tree = svg_tree(path)?;
+
+    let (width, height) = match size {
+        Size::Original | Size::Actual => (SVG_RESOLUTION, SVG_RESOLUTION),
+        Size::Thumbnail { width, height, .. } => (width, height),
+    };
+
+    let scale = (width as f32 / tree.size().width())
+        .min(height as f32 / tree.size().height());
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "empty SVG");
+
+    let mut pixmap = tiny_skia::Pixmap::new(
+        ((tree.size().width() * scale).round() as u32).max(1),
+        ((tree.size().height() * scale).round() as u32).max(1),
+    )
+    .ok_or_else(invalid)?;
+
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // The pixmap is premultiplied, unlike the rest of the pipeline
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    Ok(RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixels)
+        .ok_or_else(invalid)?)
+}
+
+#[cfg(feature = "heic")]
+fn is_heif(path: &Path) -> bool {
+    has_extension(path, &["heic", "heif"])
+}
+
+/// Decodes the primary image of a HEIC/HEIF file into RGBA, the way iPhones
+/// export photos. libheif already applies the rotation stored in the file.
+#[cfg(feature = "heic")]
+fn decode_heif(path: &Path) -> Result<DynamicImage, Error> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let context = HeifContext::read_from_file(&path.to_string_lossy())?;
+    let handle = context.primary_image_handle()?;
+    let image = LibHeif::new().decode(
+        &handle,
+        ColorSpace::Rgb(RgbChroma::Rgba),
+        None,
+    )?;
+
+    let invalid = || {
+        io::Error::new(io::ErrorKind::InvalidData, "no interleaved RGBA plane")
+    };
+
+    let plane = image.planes().interleaved.ok_or_else(invalid)?;
+    let row = plane.width as usize * 4;
+
+    // Rows may be padded beyond their pixels
+    let pixels: Vec<u8> = plane
+        .data
+        .chunks(plane.stride)
+        .take(plane.height as usize)
+        .flat_map(|line| &line[..row])
+        .copied()
+        .collect();
+
+    let buffer = RgbaImage::from_raw(plane.width, plane.height, pixels)
+        .ok_or_else(invalid)?;
+
+    Ok(DynamicImage::ImageRgba8(buffer))
+}
+
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
 )]
 pub struct Id(u32);
 
+impl Id {
+    /// Creates an [`Id`] that is unique across every listing, so images from
+    /// different directories never collide.
+    pub fn unique() -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+
+        Self(NEXT.fetch_add(1, atomic::Ordering::Relaxed))
+    }
+
+}
+
+/// The [`Id`]s of the files listed by a gallery, shared by every path leading
+/// to the same file and kept across rescans, so they keep the favorites and
+/// the selection.
+///
+/// Clones share the same [`Id`]s, so listings running in the background
+/// assign them too.
+#[derive(Debug, Clone, Default)]
+pub struct Ids(Arc<Mutex<HashMap<PathBuf, Id>>>);
+
+impl Ids {
+    /// The [`Id`] of the file at `path`, assigning a new one if no path
+    /// leading to it was seen before.
+    pub fn of(&self, path: &Path) -> Id {
+        if let Some(id) = self.get(path) {
+            return id;
+        }
+
+        // Resolved before locking, since it touches the file system
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+
+        let mut ids = self.lock();
+        let id = *ids.entry(canonical).or_insert_with(Id::unique);
+
+        let _ = ids.insert(path.to_owned(), id);
+        id
+    }
+
+    /// The [`Id`] already given to `path`, without touching the file system.
+    pub fn get(&self, path: &Path) -> Option<Id> {
+        self.lock().get(path).copied()
+    }
+
+    /// Moves every path leading to the file `id` to its new `name`, after
+    /// it is renamed within its folder.
+    pub fn rename(&self, id: Id, name: &OsStr) {
+        let mut ids = self.lock();
+
+        let renamed: Vec<PathBuf> = ids
+            .iter()
+            .filter(|(_, candidate)| **candidate == id)
+            .map(|(path, _)| path.with_file_name(name))
+            .collect();
+
+        ids.retain(|_, candidate| *candidate != id);
+        ids.extend(renamed.into_iter().map(|path| (path, id)));
+    }
+
+    /// Forgets every path leading to the file `id`, once it is gone.
+    pub fn forget(&self, id: Id) {
+        self.lock().retain(|_, candidate| *candidate != id);
+    }
+
+    /// Forgets every file, before other ones are listed.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, Id>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
 #[derive(Clone)]
 pub struct Rgba {
     pub width: u32,
@@ -58,6 +931,221 @@ pub struct Rgba {
     pub pixels: Bytes,
 }
 
+impl From<RgbaImage> for Rgba {
+    fn from(image: RgbaImage) -> Self {
+        Self::from_image_buffer(image)
+    }
+}
+
+impl Rgba {
+    /// Takes the pixels of `image` without copying them.
+    pub fn from_image_buffer(image: RgbaImage) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            pixels: Bytes::from(image.into_raw()),
+        }
+    }
+
+    /// Copies the pixels into an image buffer, or `None` if there are not
+    /// `width × height` of them.
+    pub fn to_image_buffer(&self) -> Option<RgbaImage> {
+        RgbaImage::from_raw(self.width, self.height, self.pixels.to_vec())
+    }
+
+    /// The red, green, blue and alpha channels of the pixel at `x`, `y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside the image.
+    pub fn get_pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.get_pixel_checked(x, y).unwrap_or_else(|| {
+            panic!(
+                "pixel ({x}, {y}) is outside of the {} × {} image",
+                self.width, self.height
+            )
+        })
+    }
+
+    /// The channels of the pixel at `x`, `y`, or `None` if it is outside
+    /// the image.
+    pub fn get_pixel_checked(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+
+        self.pixels.get(start..start + 4)?.try_into().ok()
+    }
+
+    /// Rotates the pixels by a quarter turn.
+    pub fn rotate(&self, clockwise: bool) -> Self {
+        let Some(buffer) = self.to_image_buffer() else {
+            return self.clone();
+        };
+
+        let rotated = if clockwise {
+            imageops::rotate90(&buffer)
+        } else {
+            imageops::rotate270(&buffer)
+        };
+
+        Self::from_image_buffer(rotated)
+    }
+
+    /// Rotates the pixels clockwise by `quarter_turns`.
+    pub fn turn(&self, quarter_turns: u8) -> Self {
+        match quarter_turns % 4 {
+            0 => self.clone(),
+            1 => self.rotate(true),
+            3 => self.rotate(false),
+            _ => self.to_image_buffer().map_or_else(
+                || self.clone(),
+                |buffer| Self::from_image_buffer(imageops::rotate180(&buffer)),
+            ),
+        }
+    }
+}
+
+impl Rgba {
+    /// Places the pixels on the system clipboard as a bitmap.
+    pub async fn copy_to_clipboard(self) -> Result<(), Error> {
+        task::spawn_blocking(move || {
+            let mut clipboard =
+                CLIPBOARD.lock().unwrap_or_else(PoisonError::into_inner);
+
+            let mut current = match clipboard.take() {
+                Some(current) => current,
+                None => arboard::Clipboard::new()?,
+            };
+
+            let result = current.set_image(arboard::ImageData {
+                width: self.width as usize,
+                height: self.height as usize,
+                bytes: Cow::Borrowed(&self.pixels),
+            });
+
+            *clipboard = Some(current);
+            result
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    /// Saves the bitmap on the system clipboard as a new PNG file in `dir`,
+    /// returning its path, or `None` if the clipboard holds no image.
+    pub async fn paste_from_clipboard(
+        dir: PathBuf,
+    ) -> Result<Option<PathBuf>, Error> {
+        task::spawn_blocking(move || {
+            let mut clipboard =
+                CLIPBOARD.lock().unwrap_or_else(PoisonError::into_inner);
+
+            let mut current = match clipboard.take() {
+                Some(current) => current,
+                None => arboard::Clipboard::new()?,
+            };
+
+            let result = current.get_image();
+            *clipboard = Some(current);
+            drop(clipboard);
+
+            let pasted = match result {
+                Ok(pasted) => pasted,
+                Err(arboard::Error::ContentNotAvailable) => return Ok(None),
+                Err(error) => return Err(error.into()),
+            };
+
+            let invalid = || {
+                io::Error::new(io::ErrorKind::InvalidData, "malformed bitmap")
+            };
+
+            let image = RgbaImage::from_raw(
+                pasted.width as u32,
+                pasted.height as u32,
+                pasted.bytes.into_owned(),
+            )
+            .ok_or_else(invalid)?;
+
+            // Never overwrite an earlier paste
+            let (path, file) = (1..)
+                .map(|n| {
+                    let name = if n == 1 {
+                        "Pasted image.png".to_owned()
+                    } else {
+                        format!("Pasted image {n}.png")
+                    };
+
+                    dir.join(name)
+                })
+                .find_map(|path| {
+                    match fs::File::create_new(&path) {
+                        Ok(file) => Some(Ok((path, file))),
+                        Err(error)
+                            if error.kind() == io::ErrorKind::AlreadyExists =>
+                        {
+                            None
+                        }
+                        Err(error) => Some(Err(error)),
+                    }
+                })
+                .expect("infinite candidates")?;
+
+            let written =
+                image.write_to(&mut io::BufWriter::new(file), ImageFormat::Png);
+
+            if let Err(error) = written {
+                let _ = fs::remove_file(&path);
+                return Err(error.into());
+            }
+
+            Ok(Some(path))
+        })
+        .await?
+    }
+
+    /// Encodes the pixels as `format` into a file at `path`, replacing it
+    /// if it exists.
+    fn write(&self, path: &Path, format: ExportFormat) -> Result<(), Error> {
+        let image = self.to_image_buffer().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "malformed bitmap")
+        })?;
+
+        let mut writer = io::BufWriter::new(fs::File::create(path)?);
+
+        let written = match format {
+            ExportFormat::Png => {
+                image.write_with_encoder(PngEncoder::new(&mut writer))
+            }
+            ExportFormat::Jpeg { quality } => {
+                let encoder =
+                    JpegEncoder::new_with_quality(&mut writer, quality);
+                let rgb = DynamicImage::ImageRgba8(image).to_rgb8();
+
+                rgb.write_with_encoder(encoder)
+            }
+            ExportFormat::WebP => image
+                .write_with_encoder(WebPEncoder::new_lossless(&mut writer)),
+        }
+        .map_err(Error::from)
+        .and_then(|()| Ok(io::Write::flush(&mut writer)?));
+
+        // Leave no half written file behind
+        if written.is_err() {
+            drop(writer);
+            let _ = fs::remove_file(path);
+        }
+
+        written
+    }
+}
+
+/// The clipboard is kept alive for the whole session, since on X11 its
+/// contents are gone as soon as it is dropped.
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
 impl fmt::Debug for Rgba {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Rgba")
@@ -67,10 +1155,141 @@ impl fmt::Debug for Rgba {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The dimensions of an original image and the size of its file.
+#[derive(Debug, Clone)]
+pub struct Details {
+    pub width: u32,
+    pub height: u32,
+    pub bytes: u64,
+    /// The description in a sidecar file next to the image, if any.
+    pub caption: Option<String>,
+}
+
+impl fmt::Display for Details {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} × {}, {}",
+            self.width,
+            self.height,
+            helper::format_bytes(self.bytes)
+        )
+    }
+}
+
+/// Where the bytes of an image are read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A file on disk, at the path of the image.
+    File,
+    /// An entry of a zip archive, read without extracting it. Such images
+    /// cannot be rotated, renamed or deleted.
+    ArchiveEntry(ArchiveEntry),
+    /// An image downloaded the first time it is decoded. Like archive
+    /// entries, it cannot be changed.
+    Remote(RemoteImage),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Size {
+    /// The full image, scaled down when it has more than
+    /// [`MAX_ORIGINAL_PIXELS`], since the viewer fits it to the window.
     Original,
-    Thumbnail { width: u32, height: u32 },
+    /// The full image at its native resolution, however large, for
+    /// inspecting it at 100%.
+    Actual,
+    Thumbnail {
+        width: u32,
+        height: u32,
+        filter: ResizeFilter,
+    },
+}
+
+impl Size {
+    /// The filter scaling the image down. Capped originals are shown
+    /// larger than thumbnails, so they always favor quality over speed.
+    fn filter(self) -> ResizeFilter {
+        match self {
+            Self::Thumbnail { filter, .. } => filter,
+            Self::Original | Self::Actual => ResizeFilter::CatmullRom,
+        }
+    }
+}
+
+/// The resampling filter used when scaling an image down, from the fastest
+/// to the sharpest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    #[default]
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    pub const ALL: [Self; 4] =
+        [Self::Nearest, Self::Triangle, Self::CatmullRom, Self::Lanczos3];
+
+    fn filter_type(self) -> FilterType {
+        match self {
+            Self::Nearest => FilterType::Nearest,
+            Self::Triangle => FilterType::Triangle,
+            Self::CatmullRom => FilterType::CatmullRom,
+            Self::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+impl fmt::Display for ResizeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Nearest => "Nearest",
+            Self::Triangle => "Triangle",
+            Self::CatmullRom => "Catmull-Rom",
+            Self::Lanczos3 => "Lanczos",
+        })
+    }
+}
+
+/// The file formats the viewer saves images as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Png,
+    /// Lossy, at a quality from 1 to 100. Transparency is dropped.
+    Jpeg { quality: u8 },
+    /// Lossless, the only kind of WebP `image` can encode.
+    WebP,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 5] = [
+        Self::Png,
+        Self::Jpeg { quality: 95 },
+        Self::Jpeg { quality: 85 },
+        Self::Jpeg { quality: 70 },
+        Self::WebP,
+    ];
+
+    /// The extension given to the files of this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg { .. } => "jpg",
+            Self::WebP => "webp",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Png => f.write_str("PNG"),
+            Self::Jpeg { quality } => write!(f, "JPEG, quality {quality}"),
+            Self::WebP => f.write_str("WebP, lossless"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +1298,55 @@ pub enum Error {
     IOFailed(Arc<io::Error>),
     JoinFailed(Arc<task::JoinError>),
     ImageDecodingFailed(Arc<image::ImageError>),
+    TrashFailed(Arc<trash::Error>),
+    ClipboardFailed(Arc<arboard::Error>),
+    SvgDecodingFailed(Arc<resvg::usvg::Error>),
+    DecodingTimedOut,
+    ArchiveFailed(Arc<zip::result::ZipError>),
+    NetworkFailed(Arc<reqwest::Error>),
+    #[cfg(feature = "heic")]
+    HeifDecodingFailed(Arc<libheif_rs::HeifError>),
+}
+
+impl Error {
+    /// Whether the file was missing, like after it was moved or deleted
+    /// behind the back of the gallery.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::IOFailed(error) if error.kind() == io::ErrorKind::NotFound
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IOFailed(error) => write!(f, "I/O failed: {error}"),
+            Self::JoinFailed(error) => write!(f, "decoding task failed: {error}"),
+            Self::ImageDecodingFailed(error) => {
+                write!(f, "decoding failed: {error}")
+            }
+            Self::TrashFailed(error) => write!(f, "trashing failed: {error}"),
+            Self::ClipboardFailed(error) => {
+                write!(f, "clipboard failed: {error}")
+            }
+            Self::SvgDecodingFailed(error) => {
+                write!(f, "decoding failed: {error}")
+            }
+            Self::DecodingTimedOut => f.write_str("decoding timed out"),
+            Self::ArchiveFailed(error) => {
+                write!(f, "reading the archive failed: {error}")
+            }
+            Self::NetworkFailed(error) => {
+                write!(f, "downloading failed: {error}")
+            }
+            #[cfg(feature = "heic")]
+            Self::HeifDecodingFailed(error) => {
+                write!(f, "decoding failed: {error}")
+            }
+        }
+    }
 }
 
 impl From<io::Error> for Error {
@@ -98,3 +1366,191 @@ impl From<image::ImageError> for Error {
         Self::ImageDecodingFailed(Arc::new(error))
     }
 }
+
+impl From<trash::Error> for Error {
+    fn from(error: trash::Error) -> Self {
+        Self::TrashFailed(Arc::new(error))
+    }
+}
+
+impl From<arboard::Error> for Error {
+    fn from(error: arboard::Error) -> Self {
+        Self::ClipboardFailed(Arc::new(error))
+    }
+}
+
+impl From<time::error::Elapsed> for Error {
+    fn from(_error: time::error::Elapsed) -> Self {
+        Self::DecodingTimedOut
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(error: zip::result::ZipError) -> Self {
+        Self::ArchiveFailed(Arc::new(error))
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Self::NetworkFailed(Arc::new(error))
+    }
+}
+
+impl From<resvg::usvg::Error> for Error {
+    fn from(error: resvg::usvg::Error) -> Self {
+        Self::SvgDecodingFailed(Arc::new(error))
+    }
+}
+
+#[cfg(feature = "heic")]
+impl From<libheif_rs::HeifError> for Error {
+    fn from(error: libheif_rs::HeifError) -> Self {
+        Self::HeifDecodingFailed(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::helper::TempDir;
+
+    use image::Rgba as Pixel;
+
+    /// A square, transparent on its left half and half opaque red on its
+    /// right half.
+    fn translucent(size: u32) -> RgbaImage {
+        RgbaImage::from_fn(size, size, |x, _| {
+            if x < size / 2 {
+                Pixel([0, 0, 0, 0])
+            } else {
+                Pixel([255, 0, 0, 128])
+            }
+        })
+    }
+
+    #[test]
+    fn decoding_keeps_transparency() {
+        let dir = TempDir::new("alpha");
+        let path = dir.join("translucent.png");
+
+        translucent(8).save(&path).unwrap();
+        let decoded = decode(&path)
+            .map(|image| fit(image, None, ResizeFilter::default()))
+            .unwrap();
+
+        assert_eq!(decoded.get_pixel(0, 0), &Pixel([0, 0, 0, 0]));
+        assert_eq!(decoded.get_pixel(7, 7), &Pixel([255, 0, 0, 128]));
+    }
+
+    #[test]
+    fn exports_read_back_turned_in_every_format() {
+        // Wider than tall, so a quarter turn shows in the dimensions
+        let image = RgbaImage::from_fn(4, 2, |x, y| {
+            Pixel([x as u8 * 60, y as u8 * 120, 0, 255])
+        });
+        let rgba = Rgba::from_image_buffer(image).turn(1);
+        let dir = TempDir::new("export");
+
+        for format in ExportFormat::ALL {
+            let path = dir.join(format!("turned.{}", format.extension()));
+
+            rgba.write(&path, format).unwrap();
+            let read = image::open(&path).unwrap().to_rgba8();
+
+            assert_eq!(read.dimensions(), (2, 4), "{format}");
+
+            if !matches!(format, ExportFormat::Jpeg { .. }) {
+                assert_eq!(read.as_raw()[..], rgba.pixels[..], "{format}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_path_to_a_file_has_the_same_id() {
+        let dir = TempDir::new("ids");
+        let nested = dir.join("nested");
+
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.join("a.png"), []).unwrap();
+        fs::write(dir.join("b.png"), []).unwrap();
+
+        let ids = Ids::default();
+        let id = ids.of(&dir.join("a.png"));
+        let through_nested = ids.of(&nested.join("..").join("a.png"));
+        let other = ids.of(&dir.join("b.png"));
+
+        assert_eq!(id, through_nested);
+        assert_ne!(id, other);
+        assert_eq!(ids.of(&dir.join("a.png")), id);
+        assert_ne!(Ids::default().of(&dir.join("a.png")), id);
+    }
+
+    #[test]
+    fn renamed_and_forgotten_files_are_rekeyed() {
+        let ids = Ids::default();
+        let id = ids.of(Path::new("/nowhere/a.png"));
+
+        ids.rename(id, OsStr::new("b.png"));
+
+        assert_eq!(ids.get(Path::new("/nowhere/a.png")), None);
+        assert_eq!(ids.get(Path::new("/nowhere/b.png")), Some(id));
+
+        ids.forget(id);
+
+        assert_eq!(ids.get(Path::new("/nowhere/b.png")), None);
+    }
+
+    #[test]
+    fn thumbnails_keep_transparency() {
+        let image = DynamicImage::ImageRgba8(translucent(64));
+        let thumbnail = fit(image, Some((16, 16)), ResizeFilter::default());
+
+        assert_eq!((thumbnail.width(), thumbnail.height()), (16, 16));
+        assert_eq!(thumbnail.get_pixel(0, 0)[3], 0);
+        assert_eq!(thumbnail.get_pixel(15, 15)[3], 128);
+    }
+
+    #[test]
+    fn captions_are_read_from_a_sidecar_of_the_same_name() {
+        let dir = TempDir::new("caption");
+
+        fs::write(dir.join("described.caption"), "  A red door\n").unwrap();
+        fs::write(dir.join("blank.txt"), "\n").unwrap();
+
+        let described = caption(&dir.join("described.jpg"));
+        let blank = caption(&dir.join("blank.jpg"));
+        let missing = caption(&dir.join("missing.jpg"));
+
+        assert_eq!(described.as_deref(), Some("A red door"));
+        assert_eq!(blank, None);
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn pixels_are_read_by_their_position() {
+        let rgba = Rgba::from_image_buffer(translucent(4));
+
+        assert_eq!(rgba.get_pixel(0, 3), [0, 0, 0, 0]);
+        assert_eq!(rgba.get_pixel(3, 0), [255, 0, 0, 128]);
+        assert_eq!(rgba.get_pixel_checked(4, 0), None);
+        assert_eq!(rgba.get_pixel_checked(0, 4), None);
+    }
+
+    #[test]
+    fn image_buffers_round_trip() {
+        let image = translucent(6);
+        let rgba = Rgba::from_image_buffer(image.clone());
+
+        assert_eq!(rgba.to_image_buffer(), Some(image));
+
+        let truncated = Rgba {
+            pixels: rgba.pixels.slice(..8),
+            ..rgba
+        };
+
+        assert_eq!(truncated.to_image_buffer(), None);
+        assert_eq!(truncated.get_pixel_checked(5, 5), None);
+    }
+}
@@ -0,0 +1,217 @@
+use exif::{Exif, In, Rational, Tag, Value};
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The EXIF details of a photo that are worth showing to a photographer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub camera: Option<String>,
+    pub lens: Option<String>,
+    pub iso: Option<String>,
+    pub shutter_speed: Option<String>,
+    pub aperture: Option<String>,
+    pub captured_at: Option<String>,
+    pub location: Option<Location>,
+    /// The description in a sidecar file next to the photo, which is not
+    /// part of its EXIF but shown along with it.
+    pub caption: Option<String>,
+}
+
+/// Where a photo was taken, in decimal degrees. South and west are negative.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Location {
+    /// A link showing the location on OpenStreetMap.
+    pub fn url(&self) -> String {
+        format!(
+            "https://www.openstreetmap.org/?mlat={lat:.6}&mlon={lon:.6}\
+             #map=15/{lat:.6}/{lon:.6}",
+            lat = self.latitude,
+            lon = self.longitude,
+        )
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.5}° {}, {:.5}° {}",
+            self.latitude.abs(),
+            if self.latitude < 0.0 { "S" } else { "N" },
+            self.longitude.abs(),
+            if self.longitude < 0.0 { "W" } else { "E" },
+        )
+    }
+}
+
+impl Metadata {
+    /// Reads the EXIF metadata of the file at `path`.
+    ///
+    /// Files without EXIF produce an empty [`Metadata`]; only failing to read
+    /// the file itself is an error.
+    pub fn read(path: &Path) -> Result<Self, io::Error> {
+        let file = fs::File::open(path)?;
+
+        Ok(Self::from_reader(&mut io::BufReader::new(file)))
+    }
+
+    /// Reads the EXIF data of an image already opened, like an entry of an
+    /// archive.
+    pub fn from_reader(reader: &mut (impl io::BufRead + io::Seek)) -> Self {
+        let Ok(exif) = exif::Reader::new().read_from_container(reader) else {
+            return Self::default();
+        };
+
+        let camera = match (field(&exif, Tag::Make), field(&exif, Tag::Model)) {
+            (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+            (Some(make), Some(model)) => Some(format!("{make} {model}")),
+            (make, model) => make.or(model),
+        };
+
+        Self {
+            camera,
+            lens: field(&exif, Tag::LensModel),
+            iso: field(&exif, Tag::PhotographicSensitivity),
+            shutter_speed: field(&exif, Tag::ExposureTime),
+            aperture: field(&exif, Tag::FNumber),
+            captured_at: field(&exif, Tag::DateTimeOriginal),
+            location: location(&exif),
+            caption: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The present entries as label and value pairs, in display order.
+    pub fn entries(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("Caption", &self.caption),
+            ("Camera", &self.camera),
+            ("Lens", &self.lens),
+            ("ISO", &self.iso),
+            ("Shutter speed", &self.shutter_speed),
+            ("Aperture", &self.aperture),
+            ("Captured", &self.captured_at),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((label, value.as_deref()?)))
+    }
+}
+
+fn field(exif: &Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let value = field
+        .display_value()
+        .with_unit(exif)
+        .to_string()
+        .trim_matches('"')
+        .trim()
+        .to_owned();
+
+    (!value.is_empty()).then_some(value)
+}
+
+fn location(exif: &Exif) -> Option<Location> {
+    let latitude =
+        coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?;
+    let longitude =
+        coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?;
+
+    let is_valid = latitude.abs() <= 90.0 && longitude.abs() <= 180.0;
+
+    is_valid.then_some(Location {
+        latitude,
+        longitude,
+    })
+}
+
+/// Reads a coordinate stored as degrees, minutes and seconds, negating it
+/// when its reference is the `negative` hemisphere.
+fn coordinate(
+    exif: &Exif,
+    tag: Tag,
+    reference: Tag,
+    negative: &str,
+) -> Option<f64> {
+    let Value::Rational(parts) = &exif.get_field(tag, In::PRIMARY)?.value
+    else {
+        return None;
+    };
+
+    // The hemisphere is unknown without its reference
+    let reference = field(exif, reference)?;
+
+    degrees(parts, &reference, negative)
+}
+
+/// Sums degrees, minutes and seconds into decimal degrees, negated when
+/// `reference` names the `negative` hemisphere.
+fn degrees(
+    parts: &[Rational],
+    reference: &str,
+    negative: &str,
+) -> Option<f64> {
+    if parts.is_empty() || parts.iter().any(|part| part.denom == 0) {
+        return None;
+    }
+
+    let value: f64 = parts
+        .iter()
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(part, divisor)| part.to_f64() / divisor)
+        .sum();
+
+    Some(if reference.eq_ignore_ascii_case(negative) {
+        -value
+    } else {
+        value
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dms(degrees: u32, minutes: u32, hundredths: u32) -> [Rational; 3] {
+        [
+            Rational::from((degrees, 1)),
+            Rational::from((minutes, 1)),
+            Rational::from((hundredths, 100)),
+        ]
+    }
+
+    #[test]
+    fn coordinates_sum_degrees_minutes_and_seconds() {
+        let latitude = degrees(&dms(40, 26, 4632), "N", "S").unwrap();
+
+        assert!((latitude - 40.4462).abs() < 1e-9);
+        assert_eq!(degrees(&dms(12, 30, 0)[..2], "E", "W"), Some(12.5));
+        assert_eq!(degrees(&[], "N", "S"), None);
+        assert_eq!(degrees(&[Rational::from((1, 0))], "N", "S"), None);
+    }
+
+    #[test]
+    fn south_and_west_are_negative() {
+        assert_eq!(degrees(&dms(33, 30, 0), "S", "S"), Some(-33.5));
+        assert_eq!(degrees(&dms(33, 30, 0), "n", "S"), Some(33.5));
+        assert_eq!(degrees(&dms(70, 15, 0), "w", "W"), Some(-70.25));
+        assert_eq!(degrees(&dms(70, 15, 0), "E", "W"), Some(70.25));
+
+        let location = Location {
+            latitude: -33.5,
+            longitude: 70.25,
+        };
+
+        assert_eq!(location.to_string(), "33.50000° S, 70.25000° E");
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::core::Size;
+
+/// How many of the latest decodes of each kind the timing covers, so a
+/// long session neither grows without bound nor summarizes a stale past.
+const CAPACITY: usize = 1000;
+
+/// The latest decodes, thumbnails first and originals second.
+static SAMPLES: Mutex<[Samples; 2]> =
+    Mutex::new([Samples::new(), Samples::new()]);
+
+/// Records that decoding an image at `size` took `duration`. Any original
+/// counts as the same size, whether it was capped or not.
+pub fn record(size: Size, duration: Duration) {
+    let index = match size {
+        Size::Thumbnail { .. } => 0,
+        Size::Original | Size::Actual => 1,
+    };
+
+    lock()[index].record(duration);
+}
+
+/// The timing of the latest thumbnails decoded, if there were any.
+pub fn thumbnails() -> Option<Stats> {
+    lock()[0].stats
+}
+
+/// The timing of the latest originals decoded, if there were any.
+pub fn originals() -> Option<Stats> {
+    lock()[1].stats
+}
+
+fn lock() -> MutexGuard<'static, [Samples; 2]> {
+    SAMPLES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The latest decodes of one kind, with their timing computed as each one
+/// is recorded rather than every time it is shown.
+struct Samples {
+    durations: VecDeque<Duration>,
+    stats: Option<Stats>,
+}
+
+impl Samples {
+    const fn new() -> Self {
+        Self {
+            durations: VecDeque::new(),
+            stats: None,
+        }
+    }
+
+    fn record(&mut self, duration: Duration) {
+        if self.durations.len() == CAPACITY {
+            let _ = self.durations.pop_front();
+        }
+
+        self.durations.push_back(duration);
+        self.stats = Stats::of(self.durations.make_contiguous());
+    }
+}
+
+/// The aggregate timing of a set of decodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub count: usize,
+    pub mean: Duration,
+    /// The time within which 95% of the decodes finished.
+    pub p95: Duration,
+}
+
+impl Stats {
+    fn of(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let count = sorted.len();
+        let total: Duration = sorted.iter().sum();
+        let rank = (count * 95).div_ceil(100).max(1);
+
+        Some(Self {
+            count,
+            mean: total / count as u32,
+            p95: sorted[rank - 1],
+        })
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} decoded, mean {:.1} ms, p95 {:.1} ms",
+            self.count,
+            self.mean.as_secs_f64() * 1000.0,
+            self.p95.as_secs_f64() * 1000.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_summarize_the_samples() {
+        let samples: Vec<Duration> =
+            (1..=100).rev().map(Duration::from_millis).collect();
+
+        let stats = Stats::of(&samples).unwrap();
+
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+        assert_eq!(stats.p95, Duration::from_millis(95));
+        assert_eq!(Stats::of(&[]), None);
+    }
+
+    #[test]
+    fn only_the_latest_samples_are_kept() {
+        let mut samples = Samples::new();
+
+        for _ in 0..CAPACITY {
+            samples.record(Duration::from_secs(1));
+        }
+
+        for _ in 0..CAPACITY / 2 {
+            samples.record(Duration::from_millis(1));
+        }
+
+        let stats = samples.stats.unwrap();
+
+        assert_eq!(samples.durations.len(), CAPACITY);
+        assert_eq!(stats.count, CAPACITY);
+        assert_eq!(stats.mean, Duration::from_micros(500_500));
+    }
+}
@@ -1,5 +1,22 @@
+mod archive;
+mod diff;
+mod histogram;
 mod image_data;
+mod metadata;
+mod orientation;
+mod remote;
+pub mod favorites;
+pub mod ratings;
+pub mod rename;
+pub mod settings;
 pub mod helper;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 
+pub use archive::{ArchiveEntry, is_archive};
+pub use diff::Diff;
+pub use histogram::Histogram;
 pub use image_data::*;
+pub use metadata::{Location, Metadata};
+pub use remote::{RemoteImage, is_url_list};
 pub use helper::*;
@@ -0,0 +1,325 @@
+//! Rotates JPEG photos by rewriting their EXIF Orientation tag, so their
+//! pixels are never re-encoded and the rest of their EXIF is kept.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The EXIF tag telling viewers how to turn the stored pixels upright.
+const ORIENTATION: u16 = 0x0112;
+
+/// The EXIF type of a 16-bit unsigned integer.
+const SHORT: u16 = 3;
+
+/// Every orientation as the clockwise quarter turns applied to the stored
+/// pixels, followed by whether they are then mirrored horizontally.
+const ORIENTATIONS: [(u16, u8, bool); 8] = [
+    (1, 0, false),
+    (6, 1, false),
+    (3, 2, false),
+    (8, 3, false),
+    (2, 0, true),
+    (5, 1, true),
+    (4, 2, true),
+    (7, 3, true),
+];
+
+/// Rotates the JPEG at `path` clockwise by `quarter_turns`, replacing the
+/// file only once the new one is fully written.
+pub fn rotate_jpeg(path: &Path, quarter_turns: u8) -> io::Result<()> {
+    let jpeg = fs::read(path)?;
+    let rotated = rotate(&jpeg, quarter_turns)?;
+
+    let temporary = path.with_extension("rotating");
+    fs::write(&temporary, rotated)?;
+
+    fs::rename(&temporary, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temporary);
+    })
+}
+
+/// The orientation showing the photo turned clockwise by `quarter_turns`
+/// from how `orientation` shows it.
+fn compose(orientation: u16, quarter_turns: u8) -> u16 {
+    let (_, turns, is_mirrored) = ORIENTATIONS
+        .into_iter()
+        .find(|(candidate, _, _)| *candidate == orientation)
+        .unwrap_or(ORIENTATIONS[0]);
+
+    // Mirroring reverses the direction of the turns applied before it
+    let turns = if is_mirrored {
+        (turns + 4 - quarter_turns % 4) % 4
+    } else {
+        (turns + quarter_turns) % 4
+    };
+
+    ORIENTATIONS
+        .into_iter()
+        .find(|(_, candidate, mirrored)| {
+            *candidate == turns && *mirrored == is_mirrored
+        })
+        .map_or(1, |(orientation, _, _)| orientation)
+}
+
+/// The JPEG turned by `quarter_turns`, updating its EXIF segment or
+/// adding one if it has none.
+fn rotate(jpeg: &[u8], quarter_turns: u8) -> io::Result<Vec<u8>> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return Err(invalid("not a JPEG file"));
+    }
+
+    let mut position = 2;
+    let mut insert_at = 2;
+
+    // The segments before the image data, looking for the EXIF one
+    while let Some(&[0xFF, marker, high, low]) =
+        jpeg.get(position..position + 4)
+    {
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+
+        let end = position + 2 + usize::from(u16::from_be_bytes([high, low]));
+        let data = jpeg.get(position + 4..end).ok_or_else(truncated)?;
+
+        if marker == 0xE1 && data.starts_with(b"Exif\0\0") {
+            let tiff = with_orientation(&data[6..], quarter_turns)?;
+
+            return splice(jpeg, position..end, &tiff);
+        }
+
+        // JFIF wants its segment first, so a new EXIF one goes after it
+        if marker == 0xE0 && position == 2 {
+            insert_at = end;
+        }
+
+        position = end;
+    }
+
+    let tiff = minimal_tiff(compose(1, quarter_turns));
+
+    splice(jpeg, insert_at..insert_at, &tiff)
+}
+
+/// Replaces `range` of the JPEG with an EXIF segment holding `tiff`.
+fn splice(
+    jpeg: &[u8],
+    range: std::ops::Range<usize>,
+    tiff: &[u8],
+) -> io::Result<Vec<u8>> {
+    let length = u16::try_from(2 + 6 + tiff.len())
+        .map_err(|_| invalid("the EXIF data is too large"))?;
+
+    let mut spliced = Vec::with_capacity(jpeg.len() + tiff.len() + 10);
+    spliced.extend_from_slice(&jpeg[..range.start]);
+    spliced.extend_from_slice(&[0xFF, 0xE1]);
+    spliced.extend_from_slice(&length.to_be_bytes());
+    spliced.extend_from_slice(b"Exif\0\0");
+    spliced.extend_from_slice(tiff);
+    spliced.extend_from_slice(&jpeg[range.end..]);
+
+    Ok(spliced)
+}
+
+/// The EXIF data turned by `quarter_turns`, with an Orientation tag
+/// added to its first directory if it has none.
+fn with_orientation(tiff: &[u8], quarter_turns: u8) -> io::Result<Vec<u8>> {
+    let is_little_endian = match tiff.get(..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Err(invalid("unknown EXIF byte order")),
+    };
+
+    let read_u16 = |at: usize| {
+        let bytes = tiff.get(at..at + 2).ok_or_else(truncated)?;
+        let bytes = [bytes[0], bytes[1]];
+
+        Ok::<_, io::Error>(if is_little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+
+    let read_u32 = |at: usize| {
+        let bytes = tiff.get(at..at + 4).ok_or_else(truncated)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+
+        Ok::<_, io::Error>(if is_little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    let u16_bytes = |value: u16| {
+        if is_little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    };
+
+    let u32_bytes = |value: u32| {
+        if is_little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    };
+
+    let directory = read_u32(4)? as usize;
+    let count = usize::from(read_u16(directory)?);
+    let entries = directory + 2;
+    let entry = |index: usize| entries + 12 * index;
+
+    let mut tiff = tiff.to_vec();
+
+    for index in 0..count {
+        if read_u16(entry(index))? != ORIENTATION {
+            continue;
+        }
+
+        if read_u16(entry(index) + 2)? != SHORT {
+            return Err(invalid("malformed orientation tag"));
+        }
+
+        let value = entry(index) + 8;
+        let orientation = compose(read_u16(value)?, quarter_turns);
+
+        tiff[value..value + 2].copy_from_slice(&u16_bytes(orientation));
+
+        return Ok(tiff);
+    }
+
+    // The directory has no room for another entry, so a copy holding one
+    // is appended and pointed to instead. Every offset stays valid, since
+    // nothing before the end moves
+    let next = read_u32(entry(count))?;
+    let position = (0..count)
+        .position(|index| {
+            read_u16(entry(index)).is_ok_and(|tag| tag > ORIENTATION)
+        })
+        .unwrap_or(count);
+
+    let mut added = Vec::with_capacity(12);
+    added.extend_from_slice(&u16_bytes(ORIENTATION));
+    added.extend_from_slice(&u16_bytes(SHORT));
+    added.extend_from_slice(&u32_bytes(1));
+    added.extend_from_slice(&u16_bytes(compose(1, quarter_turns)));
+    added.extend_from_slice(&[0, 0]);
+
+    let copied = tiff[entries..entry(count)].to_vec();
+
+    // Directories start on a word boundary
+    if tiff.len() % 2 == 1 {
+        tiff.push(0);
+    }
+
+    let moved = u32::try_from(tiff.len())
+        .map_err(|_| invalid("the EXIF data is too large"))?;
+    let count = u16::try_from(count + 1)
+        .map_err(|_| invalid("too many EXIF tags"))?;
+
+    tiff.extend_from_slice(&u16_bytes(count));
+    tiff.extend_from_slice(&copied[..12 * position]);
+    tiff.extend_from_slice(&added);
+    tiff.extend_from_slice(&copied[12 * position..]);
+    tiff.extend_from_slice(&u32_bytes(next));
+    tiff[4..8].copy_from_slice(&u32_bytes(moved));
+
+    Ok(tiff)
+}
+
+/// Big-endian EXIF data holding nothing but `orientation`.
+fn minimal_tiff(orientation: u16) -> Vec<u8> {
+    let mut tiff = Vec::with_capacity(26);
+
+    tiff.extend_from_slice(b"MM\0\x2a");
+    tiff.extend_from_slice(&8u32.to_be_bytes());
+    tiff.extend_from_slice(&1u16.to_be_bytes());
+    tiff.extend_from_slice(&ORIENTATION.to_be_bytes());
+    tiff.extend_from_slice(&SHORT.to_be_bytes());
+    tiff.extend_from_slice(&1u32.to_be_bytes());
+    tiff.extend_from_slice(&orientation.to_be_bytes());
+    tiff.extend_from_slice(&[0, 0]);
+    tiff.extend_from_slice(&0u32.to_be_bytes());
+
+    tiff
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn truncated() -> io::Error {
+    invalid("the JPEG is truncated")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use exif::{In, Tag};
+
+    /// A JPEG holding only a JFIF segment, which is all the rotation looks
+    /// at.
+    const JFIF: &[u8] = &[
+        0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xD9,
+    ];
+
+    fn orientation(jpeg: &[u8]) -> Option<u32> {
+        let exif = exif::Reader::new()
+            .read_from_container(&mut io::Cursor::new(jpeg))
+            .ok()?;
+
+        exif.get_field(Tag::Orientation, In::PRIMARY)?
+            .value
+            .get_uint(0)
+    }
+
+    #[test]
+    fn turns_compose_with_the_orientation() {
+        assert_eq!(compose(1, 1), 6);
+        assert_eq!(compose(6, 1), 3);
+        assert_eq!(compose(8, 1), 1);
+        assert_eq!(compose(3, 3), 6);
+        assert_eq!(compose(2, 1), 7);
+        assert_eq!(compose(5, 2), 7);
+        assert_eq!(compose(9, 1), 6);
+    }
+
+    #[test]
+    fn photos_without_exif_gain_an_orientation() {
+        let rotated = rotate(JFIF, 1).unwrap();
+
+        assert_eq!(&rotated[..6], &JFIF[..6], "JFIF stays first");
+        assert_eq!(orientation(&rotated), Some(6));
+
+        let rotated = rotate(&rotated, 1).unwrap();
+
+        assert_eq!(orientation(&rotated), Some(3));
+        assert!(rotated.ends_with(&[0xFF, 0xD9]));
+    }
+
+    #[test]
+    fn other_exif_tags_are_kept_when_adding_an_orientation() {
+        // A little-endian directory holding only the make of the camera
+        let mut tiff = b"II\x2a\0\x08\0\0\0".to_vec();
+        tiff.extend_from_slice(&[1, 0]);
+        tiff.extend_from_slice(&[0x0F, 0x01, 2, 0, 4, 0, 0, 0]);
+        tiff.extend_from_slice(b"Cam\0");
+        tiff.extend_from_slice(&[0, 0, 0, 0]);
+
+        let with_make = splice(JFIF, 2..2, &tiff).unwrap();
+        let rotated = rotate(&with_make, 3).unwrap();
+
+        let exif = exif::Reader::new()
+            .read_from_container(&mut io::Cursor::new(&rotated))
+            .unwrap();
+        let make = exif.get_field(Tag::Make, In::PRIMARY).unwrap();
+
+        assert_eq!(orientation(&rotated), Some(8));
+        assert_eq!(make.display_value().to_string(), "\"Cam\"");
+    }
+}
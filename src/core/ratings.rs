@@ -0,0 +1,237 @@
+use tokio::task;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::core::Error;
+
+/// The highest rating an image can be given.
+pub const MAX: u8 = 5;
+
+/// The ratings of the listed images, from 1 to [`MAX`] stars. Unrated
+/// images are left out.
+pub type Ratings = HashMap<PathBuf, u8>;
+
+/// The property holding the rating in an XMP packet.
+const PROPERTY: &str = "xmp:Rating";
+
+/// The namespace of [`PROPERTY`], declared when a rating is added to a
+/// sidecar written by another application.
+const NAMESPACE: &str = r#"xmlns:xmp="http://ns.adobe.com/xap/1.0/""#;
+
+/// Reads the ratings of the image files at `paths` from their XMP
+/// sidecars, where photo managers like darktable and Lightroom keep them
+/// too.
+///
+/// Images without a sidecar, or without a rating in it, are unrated.
+pub async fn load(paths: Vec<PathBuf>) -> Result<Ratings, Error> {
+    let ratings = task::spawn_blocking(move || {
+        let mut ratings = Ratings::new();
+
+        for path in paths {
+            let Some(sidecar) = sidecar(&path) else {
+                continue;
+            };
+
+            // An unreadable sidecar leaves only its own image unrated
+            let Ok(xmp) = fs::read(sidecar) else {
+                continue;
+            };
+
+            if let Some(rating) = rating(&String::from_utf8_lossy(&xmp)) {
+                if rating > 0 {
+                    let _ = ratings.insert(path, rating);
+                }
+            }
+        }
+
+        Ok::<_, io::Error>(ratings)
+    })
+    .await??;
+
+    Ok(ratings)
+}
+
+/// Saves the `rating` of the image file at `path` into its XMP sidecar,
+/// creating it if needed and keeping everything else it holds. A rating
+/// of 0 clears it.
+pub async fn save(path: PathBuf, rating: u8) -> Result<(), Error> {
+    task::spawn_blocking(move || {
+        let Some(sidecar) = sidecar(&path) else {
+            if rating == 0 {
+                return Ok(());
+            }
+
+            return fs::write(appended(&path), with_rating(None, rating)?);
+        };
+
+        let xmp = String::from_utf8_lossy(&fs::read(&sidecar)?).into_owned();
+
+        fs::write(sidecar, with_rating(Some(&xmp), rating)?)
+    })
+    .await??;
+
+    Ok(())
+}
+
+/// Moves the sidecar of the image file at `from` along with it to `to`,
+/// so a renamed image keeps its rating.
+pub fn move_sidecar(from: &Path, to: &Path) -> io::Result<()> {
+    let moves = [
+        (appended(from), appended(to)),
+        (from.with_extension("xmp"), to.with_extension("xmp")),
+    ];
+
+    for (from, to) in moves {
+        if from.is_file() && !to.exists() {
+            fs::rename(from, to)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The rating as filled and empty stars, like `★★★☆☆`.
+pub fn stars(rating: u8) -> String {
+    let rating = rating.min(MAX);
+
+    "★".repeat(usize::from(rating)) + &"☆".repeat(usize::from(MAX - rating))
+}
+
+/// The sidecar next to the image file at `path`, named either like
+/// `photo.jpg.xmp` or like `photo.xmp`.
+fn sidecar(path: &Path) -> Option<PathBuf> {
+    [appended(path), path.with_extension("xmp")]
+        .into_iter()
+        .find(|sidecar| sidecar.is_file())
+}
+
+/// The sidecar named after the whole file name, which is created when
+/// there is none, since images differing only in their extension do not
+/// share it.
+fn appended(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".xmp");
+
+    PathBuf::from(sidecar)
+}
+
+/// The rating in an XMP packet, stored either as an attribute or as an
+/// element. Rejected images, rated -1, are unrated.
+fn rating(xmp: &str) -> Option<u8> {
+    let value: f32 = xmp[value(xmp)?].parse().ok()?;
+
+    Some(value.clamp(0.0, f32::from(MAX)) as u8)
+}
+
+/// Where the value of the rating is in an XMP packet.
+fn value(xmp: &str) -> Option<Range<usize>> {
+    let name = xmp.find(PROPERTY)? + PROPERTY.len();
+    let start = name
+        + xmp[name..].find(|c: char| c.is_ascii_digit() || c == '-')?;
+
+    // Only the quotes of an attribute or the end of a tag come between
+    let is_separated = xmp[name..start]
+        .chars()
+        .all(|c| matches!(c, '=' | '"' | '\'' | '>') || c.is_whitespace());
+
+    if !is_separated {
+        return None;
+    }
+
+    let length = xmp[start..]
+        .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.'))
+        .unwrap_or(xmp.len() - start);
+
+    Some(start..start + length)
+}
+
+/// The XMP packet with its rating set to `rating`, or a new packet holding
+/// only the rating.
+fn with_rating(xmp: Option<&str>, rating: u8) -> io::Result<String> {
+    let rating = rating.min(MAX);
+
+    let Some(xmp) = xmp else {
+        let rdf = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+        return Ok(format!(
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n \
+             <rdf:RDF xmlns:rdf=\"{rdf}\">\n  \
+             <rdf:Description rdf:about=\"\" {NAMESPACE} \
+             {PROPERTY}=\"{rating}\"/>\n \
+             </rdf:RDF>\n\
+             </x:xmpmeta>\n"
+        ));
+    };
+
+    if let Some(value) = value(xmp) {
+        let mut xmp = xmp.to_owned();
+        xmp.replace_range(value, &rating.to_string());
+
+        return Ok(xmp);
+    }
+
+    let description = xmp.find("<rdf:Description").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the sidecar describes no image",
+        )
+    })? + "<rdf:Description".len();
+
+    let namespace = if xmp.contains("xmlns:xmp=") {
+        String::new()
+    } else {
+        format!(" {NAMESPACE}")
+    };
+
+    let mut xmp = xmp.to_owned();
+    xmp.insert_str(
+        description,
+        &format!("{namespace} {PROPERTY}=\"{rating}\""),
+    );
+
+    Ok(xmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratings_are_read_from_attributes_and_elements() {
+        let attribute = r#"<rdf:Description xmp:Rating="4"/>"#;
+        let element = "<rdf:Description><xmp:Rating>3</xmp:Rating>";
+        let rejected = r#"<rdf:Description xmp:Rating="-1"/>"#;
+
+        assert_eq!(rating(attribute), Some(4));
+        assert_eq!(rating(element), Some(3));
+        assert_eq!(rating(rejected), Some(0));
+        assert_eq!(rating(r#"<rdf:Description xmp:Label="Red"/>"#), None);
+    }
+
+    #[test]
+    fn saving_a_rating_keeps_the_rest_of_the_sidecar() {
+        let created = with_rating(None, 2).unwrap();
+
+        assert_eq!(rating(&created), Some(2));
+
+        let rated = r#"<rdf:Description dc:format="jpeg" xmp:Rating="4"/>"#;
+        let updated = with_rating(Some(rated), 1).unwrap();
+
+        assert_eq!(
+            updated,
+            r#"<rdf:Description dc:format="jpeg" xmp:Rating="1"/>"#
+        );
+
+        let unrated = r#"<rdf:Description rdf:about="" dc:format="png"/>"#;
+        let added = with_rating(Some(unrated), 5).unwrap();
+
+        assert_eq!(rating(&added), Some(5));
+        assert!(added.contains(NAMESPACE));
+        assert!(added.contains(r#"dc:format="png""#));
+        assert!(with_rating(Some("<x:xmpmeta/>"), 3).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use bytes::Bytes;
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::core::Error;
+
+/// How long a download may take before it is given up on. It is shorter
+/// than the time a decode is waited for, so a slow server fails with its
+/// own error rather than a timeout of the decode.
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The largest image downloaded, whatever size the server claims.
+const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// How many downloaded bytes are kept for the session, so scrolling back
+/// to an image or opening it in the viewer does not download it again.
+const CACHE_SIZE: usize = 256 * 1024 * 1024;
+
+static CACHE: LazyLock<Mutex<Cache>> = LazyLock::new(Mutex::default);
+
+/// The downloaded images, dropping the oldest ones once they take more than
+/// [`CACHE_SIZE`].
+#[derive(Default)]
+struct Cache {
+    images: HashMap<String, Bytes>,
+    order: VecDeque<String>,
+    size: usize,
+}
+
+impl Cache {
+    fn insert(&mut self, url: String, bytes: Bytes) {
+        self.size += bytes.len();
+
+        match self.images.insert(url.clone(), bytes) {
+            Some(replaced) => self.size -= replaced.len(),
+            None => self.order.push_back(url),
+        }
+
+        while self.size > CACHE_SIZE {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+
+            if let Some(dropped) = self.images.remove(&oldest) {
+                self.size -= dropped.len();
+            }
+        }
+    }
+}
+
+/// The result of a download, filled in by whoever started it.
+type Download = Arc<Mutex<Option<Result<Bytes, Error>>>>;
+
+/// The downloads under way, so the thumbnail, details and metadata of an
+/// image read at once share a single one.
+static DOWNLOADS: LazyLock<Mutex<HashMap<String, Download>>> =
+    LazyLock::new(Mutex::default);
+
+/// Shared by every download, so connections to the same host are reused.
+static CLIENT: LazyLock<reqwest::blocking::Client> = LazyLock::new(|| {
+    reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// Whether `path` is a `.urls` text file listing the URLs of images, one
+/// per line, browsed like a folder. Plain `.txt` files are left alone, since
+/// they are the captions of the images next to them.
+pub fn is_url_list(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("urls"))
+        && path.is_file()
+}
+
+/// Lists the URLs in the text file at `path` as paths, skipping blank lines
+/// and comments starting with `#`.
+pub fn list_image_urls(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let list = fs::read_to_string(path)?;
+
+    Ok(list
+        .lines()
+        .map(str::trim)
+        .filter(|line| RemoteImage::is_url(line))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// An image downloaded over HTTP or HTTPS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteImage {
+    pub url: String,
+}
+
+impl RemoteImage {
+    /// The remote image at `path`, if it is a URL listed by
+    /// [`list_image_urls`].
+    pub fn locate(path: &Path) -> Option<Self> {
+        let url = path.to_str()?;
+
+        Self::is_url(url).then(|| Self {
+            url: url.to_owned(),
+        })
+    }
+
+    fn is_url(line: &str) -> bool {
+        line.starts_with("http://") || line.starts_with("https://")
+    }
+
+    /// Downloads the whole image, blocking until it is received, unless it
+    /// was downloaded before. Reads of a URL already being downloaded wait
+    /// for that download instead of starting their own.
+    pub fn read(&self) -> Result<Vec<u8>, Error> {
+        if let Some(bytes) = cache().images.get(&self.url) {
+            return Ok(bytes.to_vec());
+        }
+
+        let download = Arc::clone(
+            lock(&DOWNLOADS).entry(self.url.clone()).or_default(),
+        );
+        let mut shared = lock(&download);
+
+        if let Some(result) = shared.as_ref() {
+            return result.clone().map(|bytes| bytes.to_vec());
+        }
+
+        let downloaded = self.download();
+
+        if let Ok(bytes) = &downloaded {
+            cache().insert(self.url.clone(), bytes.clone());
+        }
+
+        // Later reads find the cache, or try again after a failure
+        let _ = lock(&DOWNLOADS).remove(&self.url);
+        *shared = Some(downloaded.clone());
+
+        downloaded.map(|bytes| bytes.to_vec())
+    }
+
+    fn download(&self) -> Result<Bytes, Error> {
+        let response = CLIENT.get(&self.url).send()?.error_for_status()?;
+
+        if response.content_length().is_some_and(|length| length > MAX_SIZE) {
+            return Err(too_large());
+        }
+
+        // The length is only announced by the server, so it is enforced
+        // while reading as well
+        let mut bytes = Vec::new();
+        response.take(MAX_SIZE + 1).read_to_end(&mut bytes)?;
+
+        if bytes.len() as u64 > MAX_SIZE {
+            return Err(too_large());
+        }
+
+        Ok(Bytes::from(bytes))
+    }
+}
+
+fn cache() -> MutexGuard<'static, Cache> {
+    lock(&CACHE)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn too_large() -> Error {
+    io::Error::new(io::ErrorKind::InvalidData, "the image is too large").into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::helper::TempDir;
+
+    #[test]
+    fn only_http_urls_are_remote() {
+        let url = "https://example.com/a.png";
+        let remote = RemoteImage::locate(Path::new(url));
+
+        assert_eq!(remote.map(|remote| remote.url).as_deref(), Some(url));
+        assert_eq!(RemoteImage::locate(Path::new("/photos/a.png")), None);
+        assert_eq!(RemoteImage::locate(Path::new("ftp://example.com")), None);
+    }
+
+    #[test]
+    fn only_urls_files_are_url_lists() {
+        let dir = TempDir::new("urls");
+        let list = dir.join("photos.urls");
+        let caption = dir.join("photo.txt");
+
+        fs::write(&list, "# Holiday\nhttps://example.com/a.png\n\n").unwrap();
+        fs::write(&caption, "https://example.com/a.png\n").unwrap();
+
+        assert!(is_url_list(&list));
+        assert!(!is_url_list(&caption));
+        assert_eq!(
+            list_image_urls(&list).unwrap(),
+            [PathBuf::from("https://example.com/a.png")]
+        );
+    }
+
+    #[test]
+    fn the_cache_drops_the_oldest_downloads_first() {
+        let mut cache = Cache::default();
+        let half = Bytes::from(vec![0; CACHE_SIZE / 2]);
+
+        cache.insert("a".to_owned(), half.clone());
+        cache.insert("b".to_owned(), half.clone());
+        cache.insert("c".to_owned(), half);
+
+        assert!(!cache.images.contains_key("a"));
+        assert!(cache.images.contains_key("b"));
+        assert!(cache.images.contains_key("c"));
+        assert_eq!(cache.size, CACHE_SIZE);
+    }
+}
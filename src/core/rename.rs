@@ -0,0 +1,176 @@
+use tokio::task;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core::{Error, Id, ratings};
+
+/// Resolves `template` into a new path for each of `paths`, keeping their
+/// folders and extensions.
+///
+/// `{index}` is replaced by the position of the image in the batch, starting
+/// at 1, and `{index:03}` pads it with zeros to three digits. `{name}` is the
+/// original name of the file without its extension.
+pub fn targets<'a>(
+    template: &str,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Result<Vec<PathBuf>, TemplateError> {
+    paths
+        .into_iter()
+        .enumerate()
+        .map(|(index, path)| {
+            let mut name = stem(template, index + 1, path)?;
+
+            if let Some(extension) = path.extension() {
+                name.push('.');
+                name.push_str(&extension.to_string_lossy());
+            }
+
+            Ok(path.with_file_name(name))
+        })
+        .collect()
+}
+
+/// Renames every file to its target, refusing to overwrite anything.
+///
+/// Every target is checked before the first file is touched, and the files
+/// already renamed are moved back if a later one fails.
+pub async fn rename(
+    renames: Vec<(Id, PathBuf, PathBuf)>,
+) -> Result<Vec<(Id, PathBuf)>, Error> {
+    let renamed = task::spawn_blocking(move || {
+        let renames: Vec<_> = renames
+            .into_iter()
+            .filter(|(_, source, target)| source != target)
+            .collect();
+
+        let mut seen = HashSet::new();
+
+        for (_, _, target) in &renames {
+            if !seen.insert(target) {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!(
+                        "more than one image would be named {}",
+                        display_name(target)
+                    ),
+                ));
+            }
+
+            if target.exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", target.display()),
+                ));
+            }
+        }
+
+        for (done, (_, source, target)) in renames.iter().enumerate() {
+            if let Err(error) = fs::rename(source, target) {
+                for (_, source, target) in renames[..done].iter().rev() {
+                    let _ = fs::rename(target, source);
+                }
+
+                return Err(error);
+            }
+        }
+
+        // The ratings of the images move along with them, if they can
+        for (_, source, target) in &renames {
+            let _ = ratings::move_sidecar(source, target);
+        }
+
+        Ok(renames
+            .into_iter()
+            .map(|(id, _, target)| (id, target))
+            .collect())
+    })
+    .await??;
+
+    Ok(renamed)
+}
+
+/// Why a rename template cannot be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    Unclosed,
+    UnknownPlaceholder(String),
+    InvalidName(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unclosed => f.write_str("a placeholder is missing its }"),
+            Self::UnknownPlaceholder(placeholder) => {
+                write!(f, "unknown placeholder {{{placeholder}}}")
+            }
+            Self::InvalidName(name) if name.is_empty() => {
+                f.write_str("the name is empty")
+            }
+            Self::InvalidName(name) => write!(f, "{name} is not a valid name"),
+        }
+    }
+}
+
+/// The new name of the file at `path` without its extension.
+fn stem(
+    template: &str,
+    index: usize,
+    path: &Path,
+) -> Result<String, TemplateError> {
+    let mut stem = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        stem.push_str(&rest[..start]);
+
+        let end = rest[start..].find('}').ok_or(TemplateError::Unclosed)?;
+        let placeholder = &rest[start + 1..start + end];
+
+        match placeholder.split_once(':') {
+            None if placeholder == "index" => stem.push_str(&index.to_string()),
+            None if placeholder == "name" => stem.push_str(
+                &path
+                    .file_stem()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default(),
+            ),
+            Some(("index", width)) => {
+                let width: usize = width.parse().map_err(|_| {
+                    TemplateError::UnknownPlaceholder(placeholder.to_owned())
+                })?;
+
+                stem.push_str(&format!("{index:0width$}"));
+            }
+            _ => {
+                return Err(TemplateError::UnknownPlaceholder(
+                    placeholder.to_owned(),
+                ));
+            }
+        }
+
+        rest = &rest[start + end + 1..];
+    }
+
+    stem.push_str(rest);
+
+    if stem.is_empty()
+        || stem == "."
+        || stem == ".."
+        || stem.contains(['/', '\\'])
+    {
+        return Err(TemplateError::InvalidName(stem));
+    }
+
+    Ok(stem)
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,139 @@
+use serde_json::{Map, Value, json};
+use tokio::task;
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::core::Error;
+
+/// The preferences remembered between sessions.
+///
+/// Values are kept as the names the interface gave them, so a setting it
+/// no longer recognizes falls back to its default instead of failing the
+/// whole file. Keys it does not know, like those of a newer version, are
+/// kept when saving.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    pub sort_order: Option<String>,
+    /// The folder browsed last, offered again on the next launch.
+    pub last_folder: Option<PathBuf>,
+}
+
+impl Settings {
+    /// Loads the settings saved by a previous session, blocking until they
+    /// are read so they apply before anything is shown.
+    ///
+    /// Missing or malformed settings are all defaults.
+    pub fn load() -> Self {
+        let Some(value) = path()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+        else {
+            return Self::default();
+        };
+
+        let string = |key: &str| value.get(key)?.as_str().map(str::to_owned);
+
+        Self {
+            sort_order: string("sort_order"),
+            last_folder: string("last_folder").map(PathBuf::from),
+        }
+    }
+
+    /// Saves the settings over the ones saved before, keeping the keys
+    /// they do not know.
+    pub async fn save(self) -> Result<(), Error> {
+        task::spawn_blocking(move || {
+            let Some(path) = path() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no configuration directory",
+                ));
+            };
+
+            let saved = fs::read(&path).ok();
+            let value = self.merged_into(saved.as_deref());
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::write(path, serde_json::to_vec_pretty(&value)?)
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    /// The `saved` settings, if they are a JSON object, with these ones
+    /// written over them.
+    fn merged_into(self, saved: Option<&[u8]>) -> Map<String, Value> {
+        let mut value = match saved.map(serde_json::from_slice::<Value>) {
+            Some(Ok(Value::Object(value))) => value,
+            _ => Map::new(),
+        };
+
+        match self.sort_order {
+            Some(sort_order) => {
+                let _ = value.insert("sort_order".into(), json!(sort_order));
+            }
+            None => {
+                let _ = value.remove("sort_order");
+            }
+        }
+
+        match self.last_folder {
+            Some(last_folder) => {
+                let _ = value.insert(
+                    "last_folder".into(),
+                    json!(last_folder.to_string_lossy()),
+                );
+            }
+            None => {
+                let _ = value.remove("last_folder");
+            }
+        }
+
+        value
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("iced_image_gallery").join("settings.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_keys_are_kept_when_saving() {
+        let saved =
+            br#"{"sort_order": "name_asc", "zoom": 2, "last_folder": "/"}"#;
+        let settings = Settings {
+            sort_order: Some("name_desc".to_owned()),
+            last_folder: None,
+        };
+
+        let merged = settings.merged_into(Some(saved));
+
+        assert_eq!(
+            Value::Object(merged),
+            json!({ "sort_order": "name_desc", "zoom": 2 })
+        );
+    }
+
+    #[test]
+    fn malformed_settings_are_replaced() {
+        let settings = Settings {
+            sort_order: Some("name_asc".to_owned()),
+            last_folder: None,
+        };
+
+        let merged = settings.merged_into(Some(b"[1, 2"));
+
+        assert_eq!(Value::Object(merged), json!({ "sort_order": "name_asc" }));
+    }
+}
@@ -0,0 +1,13 @@
+//! A gallery of the images in a set of folders, built with iced.
+//!
+//! It runs on its own through the `iced_image_gallery` binary, but the
+//! [`Gallery`](ui::gallery::Gallery) can also be embedded into a larger
+//! application like any other component: forward its [`Message`]s to
+//! [`update`] and map its [`view`] and [`subscription`].
+//!
+//! [`Message`]: ui::gallery::Message
+//! [`update`]: ui::gallery::Gallery::update
+//! [`view`]: ui::gallery::Gallery::view
+//! [`subscription`]: ui::gallery::Gallery::subscription
+pub mod core;
+pub mod ui;
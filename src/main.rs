@@ -1,33 +1,89 @@
-mod core;
-mod ui;
-
-use ui::gallery::{Gallery, Message as GalleryMessage};
+use iced_image_gallery::core::settings::Settings;
+use iced_image_gallery::core::{self, ImageData};
+use iced_image_gallery::ui::gallery::{Gallery, Message as GalleryMessage};
 use iced::{Element, Theme, Task, Subscription};
-use iced::widget::{button, container, text};
+use iced::window;
+use iced::widget::{button, column, container, text};
 use std::env;
 use std::path::PathBuf;
+use std::sync::LazyLock;
+
+/// The theme matching the light or dark preference of the system, read
+/// once at startup.
+static SYSTEM_THEME: LazyLock<Theme> = LazyLock::new(|| {
+    match dark_light::detect() {
+        Ok(dark_light::Mode::Light) => Theme::TokyoNightLight,
+        Ok(dark_light::Mode::Dark | dark_light::Mode::Unspecified)
+        | Err(_) => Theme::TokyoNight,
+    }
+});
 
 pub enum State {
-    Landing { image_dir: String },
+    Landing {
+        image_dirs: Vec<PathBuf>,
+        /// The lowercase extensions of the files listed as images.
+        extensions: Vec<String>,
+        /// The most images listed, if capped on the command line.
+        limit: Option<usize>,
+        /// The folder browsed in the last session, if any.
+        last_folder: Option<PathBuf>,
+    },
     Gallery(Gallery),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     LoadGallery,
+    ChooseFolder,
+    FolderChosen(Option<PathBuf>),
+    ReopenLastFolder,
     GalleryMessage(GalleryMessage),
 }
 
 fn update(state: &mut State, message: Message) -> Task<Message> {
     match (&mut *state, message) {
-        (State::Landing { image_dir }, Message::LoadGallery) => {
-            let gallery = Gallery::new();
-            let path = PathBuf::from(image_dir.clone());
-            *state = State::Gallery(gallery);
-            Task::perform(
-                async move { GalleryMessage::OpenImageDirectory(path) },
-                Message::GalleryMessage,
-            )
+        (
+            State::Landing {
+                image_dirs,
+                extensions,
+                limit,
+                ..
+            },
+            Message::LoadGallery,
+        ) => {
+            let (paths, extensions) = (image_dirs.clone(), extensions.clone());
+            let limit = *limit;
+            open_gallery(state, paths, extensions, limit)
+        }
+        (State::Landing { .. }, Message::ChooseFolder) => Task::perform(
+            async {
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose an image folder")
+                    .pick_folder()
+                    .await
+                    .map(|folder| folder.path().to_path_buf())
+            },
+            Message::FolderChosen,
+        ),
+        (
+            State::Landing { extensions, limit, .. },
+            Message::FolderChosen(Some(path)),
+        ) => {
+            let (extensions, limit) = (extensions.clone(), *limit);
+            open_gallery(state, vec![path], extensions, limit)
+        }
+        (
+            State::Landing {
+                last_folder: Some(folder),
+                extensions,
+                limit,
+                ..
+            },
+            Message::ReopenLastFolder,
+        ) => {
+            let (path, extensions) = (folder.clone(), extensions.clone());
+            let limit = *limit;
+            open_gallery(state, vec![path], extensions, limit)
         }
         (State::Gallery(gallery), Message::GalleryMessage(gallery_msg)) => {
             gallery.update(gallery_msg).map(Message::GalleryMessage)
@@ -36,13 +92,67 @@ fn update(state: &mut State, message: Message) -> Task<Message> {
     }
 }
 
+fn open_gallery(
+    state: &mut State,
+    paths: Vec<PathBuf>,
+    extensions: Vec<String>,
+    limit: Option<usize>,
+) -> Task<Message> {
+    let (gallery, task) =
+        Gallery::new(paths, extensions, Settings::load(), limit);
+
+    *state = State::Gallery(gallery);
+    task.map(Message::GalleryMessage)
+}
+
 fn view(state: &State) -> Element<Message> {
     match state {
-        State::Landing { .. } => {
+        State::Landing {
+            image_dirs,
+            last_folder,
+            ..
+        } => {
+            if !image_dirs.is_empty() {
+                let action =
+                    button(text("Load Images")).on_press(Message::LoadGallery);
+
+                return container(action.padding(10))
+                    .align_x(iced::Alignment::Center)
+                    .align_y(iced::Alignment::Center)
+                    .into();
+            }
+
+            let choose = button(text("Choose folder…"))
+                .on_press(Message::ChooseFolder)
+                .padding(10);
+
+            // A folder moved or deleted since is shown but cannot be opened
+            let reopen = last_folder.as_ref().map(|folder| {
+                let exists = folder.is_dir();
+
+                let reopen =
+                    button(text(format!("Reopen {}", folder.display())))
+                        .on_press_maybe(
+                            exists.then_some(Message::ReopenLastFolder),
+                        )
+                        .style(button::secondary)
+                        .padding(10);
+
+                column![reopen]
+                    .push_maybe(
+                        (!exists).then(|| {
+                            text("This folder no longer exists").size(12)
+                        }),
+                    )
+                    .spacing(4)
+                    .align_x(iced::Alignment::Center)
+            });
+
             container(
-                button(text("Load Images"))
-                    .on_press(Message::LoadGallery)
-                    .padding(10)
+                column![choose]
+                    .push_maybe(reopen)
+                    .spacing(10)
+                    .align_x(iced::Alignment::Center),
             )
             .align_x(iced::Alignment::Center)
             .align_y(iced::Alignment::Center)
@@ -54,6 +164,22 @@ fn view(state: &State) -> Element<Message> {
     }
 }
 
+fn theme(state: &State) -> Theme {
+    match state {
+        State::Landing { .. } => SYSTEM_THEME.clone(),
+        State::Gallery(gallery) => {
+            gallery.theme().unwrap_or_else(|| SYSTEM_THEME.clone())
+        }
+    }
+}
+
+fn title(state: &State) -> String {
+    match state {
+        State::Landing { .. } => String::from("Gallery - Iced"),
+        State::Gallery(gallery) => gallery.title(),
+    }
+}
+
 fn subscription(state: &State) -> Subscription<Message> {
     match state {
         State::Landing { .. } => Subscription::none(),
@@ -61,19 +187,135 @@ fn subscription(state: &State) -> Subscription<Message> {
     }
 }
 
+struct Args {
+    image_dirs: Vec<PathBuf>,
+    decoders: Option<usize>,
+    extensions: Vec<String>,
+    fullscreen: bool,
+    limit: Option<usize>,
+    software: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut parsed = Args {
+        image_dirs: Vec::new(),
+        decoders: None,
+        extensions: core::default_extensions(),
+        fullscreen: false,
+        limit: None,
+        software: false,
+    };
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--decoders" => {
+                let value = args.next().ok_or("--decoders expects a count")?;
+                let decoders = value
+                    .parse()
+                    .ok()
+                    .filter(|decoders| *decoders > 0)
+                    .ok_or_else(|| format!("invalid decoder count: {value}"))?;
+
+                parsed.decoders = Some(decoders);
+            }
+            "--extensions" => {
+                let value = args
+                    .next()
+                    .ok_or("--extensions expects a comma-separated list")?;
+
+                parsed.extensions = parse_extensions(&value)?;
+            }
+            "--fullscreen" => parsed.fullscreen = true,
+            "--limit" => {
+                let value = args.next().ok_or("--limit expects a count")?;
+                let limit = value
+                    .parse()
+                    .ok()
+                    .filter(|limit| *limit > 0)
+                    .ok_or_else(|| format!("invalid limit: {value}"))?;
+
+                parsed.limit = Some(limit);
+            }
+            "--software" => parsed.software = true,
+            _ => parsed.image_dirs.push(PathBuf::from(arg)),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Parses a list like `jpg,png,cr2` into lowercase extensions, ignoring
+/// leading dots.
+fn parse_extensions(value: &str) -> Result<Vec<String>, String> {
+    let mut extensions = Vec::new();
+
+    for extension in value.split(',') {
+        let extension = extension.trim().trim_start_matches('.');
+
+        if extension.is_empty()
+            || !extension.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(format!("invalid extension list: {value}"));
+        }
+
+        let extension = extension.to_ascii_lowercase();
+
+        if !extensions.contains(&extension) {
+            extensions.push(extension);
+        }
+    }
+
+    Ok(extensions)
+}
+
 fn main() -> iced::Result {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <image_directory>", args[0]);
+    let args = parse_args().unwrap_or_else(|error| {
+        eprintln!("{error}");
+        eprintln!(
+            "Usage: {} [--decoders <count>] [--extensions <list>] \
+             [--fullscreen] [--limit <count>] [--software] \
+             [<image_directory>...]",
+            env!("CARGO_PKG_NAME")
+        );
         std::process::exit(1);
+    });
+
+    // Drawing on the CPU is slower, above all when scrolling a grid of
+    // large thumbnails, but works where no GPU can be set up, like on
+    // virtual machines and CI boxes. Setting ICED_BACKEND=tiny-skia has the
+    // same effect
+    if args.software {
+        // SAFETY: No other thread has been started yet
+        unsafe { env::set_var("ICED_BACKEND", "tiny-skia") };
+    }
+
+    if let Some(decoders) = args.decoders {
+        ImageData::set_thumbnail_decoders(decoders);
     }
 
-    let image_dir = args[1].clone();
-    iced::application("Gallery - Iced", update, view)
+    // Detecting the preference may block on the desktop portal
+    let _ = LazyLock::force(&SYSTEM_THEME);
+
+    let image_dirs = args.image_dirs;
+    let extensions = args.extensions;
+    let limit = args.limit;
+    let last_folder = Settings::load().last_folder;
+    iced::application(title, update, view)
         .subscription(subscription)
-        .theme(|_| Theme::TokyoNight)
+        .theme(theme)
+        .window(window::Settings {
+            fullscreen: args.fullscreen,
+            ..window::Settings::default()
+        })
         .run_with(move || {
-            let state = State::Landing { image_dir };
+            let state = State::Landing {
+                image_dirs,
+                extensions,
+                limit,
+                last_folder,
+            };
             (state, Task::none())
         })
-} 
\ No newline at end of file
+}
@@ -0,0 +1,46 @@
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry};
+use iced::{Point, Rectangle, Renderer, Size, Theme, color};
+
+/// Draws the light squares of a checkerboard, revealing the transparent
+/// regions of the image in front of it.
+pub struct Checkerboard {
+    pub opacity: f32,
+}
+
+impl Checkerboard {
+    const SQUARE_SIZE: f32 = 16.0;
+}
+
+impl<Message> canvas::Program<Message> for Checkerboard {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        let columns = (bounds.width / Self::SQUARE_SIZE).ceil() as usize;
+        let rows = (bounds.height / Self::SQUARE_SIZE).ceil() as usize;
+
+        for row in 0..rows {
+            for column in (row % 2..columns).step_by(2) {
+                frame.fill_rectangle(
+                    Point::new(
+                        column as f32 * Self::SQUARE_SIZE,
+                        row as f32 * Self::SQUARE_SIZE,
+                    ),
+                    Size::new(Self::SQUARE_SIZE, Self::SQUARE_SIZE),
+                    color!(0x999999, self.opacity),
+                );
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,56 @@
+use crate::core::Id;
+use iced::widget::{button, column, container, mouse_area, opaque, text};
+use iced::{Element, Fill, Padding, Point, Size};
+
+use crate::ui::gallery::Message;
+
+const WIDTH: f32 = 180.0;
+const ITEM_HEIGHT: f32 = 32.0;
+
+/// A floating menu with the actions for a single image, opened at
+/// `position` and closed by pressing anywhere outside of it.
+pub fn context_menu<'a>(
+    id: Id,
+    position: Point,
+    is_favorite: bool,
+    window: Size,
+) -> Element<'a, Message> {
+    let items = [
+        ("Open", Message::Open(id)),
+        ("Copy", Message::CopyImage(id)),
+        ("Copy path", Message::CopyPath(id)),
+        ("Reveal in file manager", Message::RevealInFiles(id)),
+        ("Delete", Message::Delete(id)),
+        (
+            if is_favorite { "Unfavorite" } else { "Favorite" },
+            Message::ToggleFavorite(id),
+        ),
+    ];
+
+    let height = ITEM_HEIGHT * items.len() as f32;
+
+    let menu = column(items.into_iter().map(|(label, message)| {
+        button(text(label))
+            .on_press(Message::ContextMenuSelected(Box::new(message)))
+            .width(Fill)
+            .height(ITEM_HEIGHT)
+            .style(button::text)
+            .into()
+    }))
+    .width(WIDTH);
+
+    let menu = container(menu).padding(4).style(container::rounded_box);
+
+    // Keep the whole menu inside the window
+    let padding = Padding {
+        top: position.y.min(window.height - height).max(0.0),
+        left: position.x.min(window.width - WIDTH).max(0.0),
+        ..Padding::ZERO
+    };
+
+    opaque(
+        mouse_area(container(menu).width(Fill).height(Fill).padding(padding))
+            .on_press(Message::CloseContextMenu)
+            .on_right_press(Message::CloseContextMenu),
+    )
+}
@@ -0,0 +1,89 @@
+use iced::widget::{center, column, container, mouse_area, opaque, row, text};
+use iced::{color, Element, Fill};
+
+use crate::ui::gallery::Message;
+
+const WIDTH: u32 = 520;
+const KEY_WIDTH: u32 = 160;
+
+const GRID: &[(&str, &str)] = &[
+    ("Arrow keys", "Move the focus between the cards"),
+    ("Enter", "Open the focused image"),
+    ("Click", "Select, with Shift for a range and Ctrl for more"),
+    ("Double click", "Open the image"),
+    ("C", "Compare the two selected images"),
+    ("D", "Show the difference of the two selected images"),
+    ("Ctrl+V", "Paste an image from the clipboard"),
+];
+
+const VIEWER: &[(&str, &str)] = &[
+    ("← →", "Previous and next image"),
+    ("Page Up, Page Down", "Skip several images"),
+    ("Home, End", "First and last image"),
+    ("Scroll, drag", "Zoom in and pan"),
+    ("F", "Switch between fit and actual size"),
+    ("[ ]", "Rotate left and right"),
+    ("Enter", "Save the rotation"),
+    ("I", "Show the metadata"),
+    ("H", "Show the histogram"),
+    ("N", "Show the caption"),
+    ("T", "Show the filmstrip"),
+    ("1 – 5", "Rate the image"),
+    ("0", "Clear the rating"),
+    ("B", "Change the backdrop"),
+    ("Ctrl+C", "Copy the image"),
+    ("Ctrl+S", "Save a copy in the format picked in the toolbar"),
+    ("P", "Copy the path of the file"),
+    ("R", "Reveal the file in the file manager"),
+    ("Delete", "Move the file to the trash"),
+    ("Escape", "Close the viewer"),
+];
+
+const WINDOW: &[(&str, &str)] = &[
+    ("F11", "Toggle fullscreen"),
+    #[cfg(feature = "metrics")]
+    ("F12", "Show the decode timings"),
+    ("?", "Show this help"),
+];
+
+/// A modal listing every keyboard shortcut, closed by pressing anywhere,
+/// `?` or Escape.
+pub fn help<'a>() -> Element<'a, Message> {
+    let dialog = column![
+        text("Keyboard shortcuts").size(20),
+        section("Grid", GRID),
+        section("Viewer", VIEWER),
+        section("Window", WINDOW),
+        text("Press ? or Escape to close.").size(12),
+    ]
+    .spacing(15);
+
+    opaque(
+        mouse_area(
+            center(
+                container(dialog)
+                    .padding(20)
+                    .max_width(WIDTH)
+                    .style(container::rounded_box),
+            )
+            .style(|_theme| {
+                container::Style::default().background(color!(0x000000, 0.5))
+            }),
+        )
+        .on_press(Message::HelpToggled),
+    )
+}
+
+fn section<'a>(
+    title: &'a str,
+    bindings: &'a [(&'a str, &'a str)],
+) -> Element<'a, Message> {
+    column![text(title).size(16)]
+        .extend(bindings.iter().map(|(key, action)| {
+            row![text(*key).width(KEY_WIDTH), text(*action).width(Fill)]
+                .spacing(10)
+                .into()
+        }))
+        .spacing(5)
+        .into()
+}
@@ -0,0 +1,64 @@
+use crate::core::Histogram;
+use iced::mouse;
+use iced::widget::canvas::{self, Cache, Frame, Geometry, Path, Stroke};
+use iced::{Color, Point, Rectangle, Renderer, Theme, color};
+
+/// Draws the channels of a [`Histogram`] on top of each other.
+pub struct Chart<'a> {
+    pub histogram: &'a Histogram,
+    pub cache: &'a Cache,
+}
+
+impl<Message> canvas::Program<Message> for Chart<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let chart = self.cache.draw(renderer, bounds.size(), |frame| {
+            let peak = self.histogram.peak().max(1) as f32;
+
+            let channels = [
+                (&self.histogram.red, color!(0xff4040, 0.5)),
+                (&self.histogram.green, color!(0x40ff40, 0.5)),
+                (&self.histogram.blue, color!(0x4080ff, 0.5)),
+            ];
+
+            for (bins, color) in channels {
+                frame.fill(&area(frame, bins, peak), color);
+            }
+
+            frame.stroke(
+                &area(frame, &self.histogram.luminance, peak),
+                Stroke::default().with_color(Color::WHITE).with_width(1.0),
+            );
+        });
+
+        vec![chart]
+    }
+}
+
+/// The closed outline under the bins, scaled so the `peak` reaches the top.
+fn area(frame: &Frame, bins: &[u32; 256], peak: f32) -> Path {
+    let size = frame.size();
+    let step = size.width / (bins.len() - 1) as f32;
+
+    Path::new(|builder| {
+        builder.move_to(Point::new(0.0, size.height));
+
+        for (index, count) in bins.iter().enumerate() {
+            builder.line_to(Point::new(
+                index as f32 * step,
+                size.height * (1.0 - *count as f32 / peak),
+            ));
+        }
+
+        builder.line_to(Point::new(size.width, size.height));
+        builder.close();
+    })
+}
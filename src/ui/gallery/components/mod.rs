@@ -1,5 +1,17 @@
+mod checkerboard;
+mod context_menu;
+mod help;
+mod histogram;
 mod preview;
+mod rename;
+mod toast;
 mod viewer;
 
-pub use preview::{card, placeholder, Preview, Thumbnail};
+pub use context_menu::context_menu;
+pub use help::help;
+pub use preview::{
+    card, placeholder, Preview, Thumbnail, ThumbnailFit, ThumbnailSize,
+};
+pub use rename::rename_dialog;
+pub use toast::Toast;
 pub use viewer::Viewer; 
\ No newline at end of file
@@ -1,52 +1,195 @@
-use crate::core::{Id, ImageData, Rgba};
+use crate::core::ratings;
+use crate::core::{Details, Error, Id, ImageData, Rgba};
 use iced::animation;
+use iced::mouse;
 use iced::time::Instant;
-use iced::widget::{button, container, horizontal_space, image, mouse_area, pop};
-use iced::{ContentFit, Element, Fill, Theme, Animation};
+use iced::widget::{
+    button, center, column, container, horizontal_space, image, mouse_area,
+    pop, stack, text, tooltip,
+};
+use iced::{Border, Center, ContentFit, Element, Fill, Theme, Animation};
+use iced::{border, color};
 
-use crate::ui::gallery::Message;
+use crate::ui::gallery::{AnimationSpeed, Message};
+
+use std::fmt;
+
+/// The size of the pulsing dot shown while a thumbnail decodes.
+const SPINNER_SIZE: u32 = 24;
 
 pub fn card<'a>(
     metadata: &'a ImageData,
     preview: Option<&'a Preview>,
+    details: Option<&Result<Details, Error>>,
+    width: u32,
+    height: u32,
+    fit: ThumbnailFit,
+    is_selected: bool,
+    is_focused: bool,
+    is_favorite: bool,
+    rating: u8,
+    shimmer: f32,
     now: Instant,
 ) -> Element<'a, Message> {
-    let image = if let Some(preview) = preview {
-        let thumbnail: Element<'_, _> =
-            if let Preview::Ready { thumbnail, .. } = &preview {
-                image(&thumbnail.handle)
-                    .width(Fill)
-                    .height(Fill)
-                    .content_fit(ContentFit::Contain)
-                    .opacity(thumbnail.fade_in.interpolate(0.0, 1.0, now))
-                    .scale(thumbnail.zoom.interpolate(1.0, 1.02, now))
-                    .into()
-            } else {
-                horizontal_space().into()
-            };
-
-        thumbnail
-    } else {
-        horizontal_space().into()
+    let image: Element<'_, _> = match preview {
+        Some(Preview::Ready { thumbnail, .. }) => image(&thumbnail.handle)
+            .width(Fill)
+            .height(Fill)
+            .content_fit(fit.content_fit())
+            .opacity(thumbnail.fade_in.interpolate(0.0, 1.0, now))
+            .scale(thumbnail.zoom.interpolate(1.0, 1.02, now))
+            .into(),
+        Some(Preview::Failed { reason }) => center(
+            column![
+                text(match reason {
+                    Error::IOFailed(_) => "Cannot read file",
+                    Error::DecodingTimedOut => "Took too long to load",
+                    Error::NetworkFailed(_) => "Cannot download",
+                    _ => "Failed to load",
+                })
+                .size(18),
+                text(metadata.file_name()),
+                text(reason.to_string()).size(12),
+            ]
+            .spacing(5)
+            .padding(10)
+            .align_x(Center),
+        )
+        .into(),
+        Some(Preview::Incomplete) => center(
+            column![
+                text("Incomplete file").size(18),
+                text(metadata.file_name()),
+            ]
+            .spacing(5)
+            .padding(10)
+            .align_x(Center),
+        )
+        .into(),
+        Some(Preview::Loading) => center(
+            container(horizontal_space())
+                .width(SPINNER_SIZE)
+                .height(SPINNER_SIZE)
+                .style(move |_theme| {
+                    let opacity = 0.2 + 0.7 * shimmer;
+
+                    container::Style {
+                        background: Some(color!(0xffffff, opacity).into()),
+                        border: border::rounded(SPINNER_SIZE / 2),
+                        ..container::Style::default()
+                    }
+                }),
+        )
+        .into(),
+        None => horizontal_space().into(),
     };
 
+    let has_failed = matches!(preview, Some(Preview::Failed { .. }));
+
+    let favorite = container(
+        button(text(if is_favorite { "♥" } else { "♡" }).size(20))
+            .on_press(Message::ToggleFavorite(metadata.id))
+            .style(button::text),
+    )
+    .align_right(Fill)
+    .align_top(Fill)
+    .padding(5);
+
+    let rating = (rating > 0).then(|| {
+        container(
+            container(text(ratings::stars(rating)).size(14))
+                .padding([2, 6])
+                .style(|_theme| container::Style {
+                    background: Some(color!(0x000000, 0.6).into()),
+                    text_color: Some(color!(0xffd700)),
+                    border: border::rounded(4),
+                    ..container::Style::default()
+                }),
+        )
+        .align_left(Fill)
+        .align_bottom(Fill)
+        .padding(5)
+    });
+
     let card = mouse_area(
-        container(image)
-            .width(Preview::WIDTH)
-            .height(Preview::HEIGHT)
-            .style(container::dark),
+        container(stack![image, favorite].push_maybe(rating))
+            .width(width)
+            .height(height)
+            .style(move |theme| {
+                let style = container::dark(theme);
+
+                if is_focused {
+                    container::Style {
+                        border: Border {
+                            color: theme.palette().text,
+                            width: 3.0,
+                            radius: style.border.radius,
+                        },
+                        ..style
+                    }
+                } else if is_selected {
+                    container::Style {
+                        border: Border {
+                            color: theme.extended_palette().primary.strong.color,
+                            width: 3.0,
+                            radius: style.border.radius,
+                        },
+                        ..style
+                    }
+                } else if has_failed {
+                    container::Style {
+                        border: Border {
+                            color: theme.extended_palette().danger.weak.color,
+                            width: 1.0,
+                            radius: style.border.radius,
+                        },
+                        ..style
+                    }
+                } else {
+                    style
+                }
+            }),
     )
+    .on_press(Message::ToggleSelect(metadata.id))
+    .on_right_press(Message::ContextMenu(metadata.id))
     .on_enter(Message::ThumbnailHovered(metadata.id, true))
-    .on_exit(Message::ThumbnailHovered(metadata.id, false));
+    .on_exit(Message::ThumbnailHovered(metadata.id, false))
+    .interaction(mouse::Interaction::Pointer);
 
-    if let Some(preview) = preview {
-        let is_thumbnail = matches!(preview, Preview::Ready { .. });
+    let card: Element<'_, _> = if let Some(details) = details {
+        let lines = match details {
+            Ok(details) => column![text(details.to_string()).size(14)]
+                .push_maybe(
+                    details
+                        .caption
+                        .as_ref()
+                        .map(|caption| text(caption.clone()).size(14)),
+                ),
+            Err(error) => column![
+                text("Details unavailable").size(14),
+                text(error.to_string()).size(12),
+            ],
+        };
 
-        button(card)
-            .on_press_maybe(is_thumbnail.then_some(Message::Open(metadata.id)))
-            .padding(0)
-            .style(button::text)
-            .into()
+        tooltip(
+            card,
+            container(lines.spacing(5))
+            .max_width(320)
+            .padding(5)
+            .style(container::rounded_box),
+            tooltip::Position::FollowCursor,
+        )
+        .into()
+    } else {
+        card.into()
+    };
+
+    // Evicted thumbnails are loaded again once their card is shown
+    if let Some(
+        Preview::Ready { .. } | Preview::Failed { .. } | Preview::Incomplete,
+    ) = preview
+    {
+        card
     } else {
         pop(card)
             .on_show(|_| Message::ImagePoppedIn(metadata.id))
@@ -54,11 +197,20 @@ pub fn card<'a>(
     }
 }
 
-pub fn placeholder<'a>() -> Element<'a, Message> {
+/// An empty card, brightening with `shimmer` from 0 to 1 to show it is
+/// loading.
+pub fn placeholder<'a>(size: u32, shimmer: f32) -> Element<'a, Message> {
     container(horizontal_space())
-        .width(Preview::WIDTH)
-        .height(Preview::HEIGHT)
-        .style(container::dark)
+        .width(size)
+        .height(size)
+        .style(move |theme: &Theme| {
+            let color = theme.extended_palette().background.strong.color;
+
+            container::Style {
+                background: Some(color.scale_alpha(0.5 + 0.5 * shimmer).into()),
+                ..container::dark(theme)
+            }
+        })
         .into()
 }
 
@@ -68,34 +220,111 @@ pub enum Preview {
     Ready {
         thumbnail: Thumbnail,
     },
+    /// The file exists, but could not be read or decoded.
+    Failed {
+        reason: Error,
+    },
+    /// The file was empty when it was listed.
+    Incomplete,
 }
 
 #[derive(Debug, Clone)]
 pub struct Thumbnail {
     pub handle: image::Handle,
+    /// The approximate memory held by the decoded pixels.
+    pub bytes: usize,
     pub fade_in: Animation<bool>,
     pub zoom: Animation<bool>,
 }
 
-impl Preview {
-    pub const WIDTH: u32 = 360;
-    pub const HEIGHT: u32 = 360;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThumbnailSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
 
-    pub fn ready(rgba: Rgba) -> Self {
-        Self::Ready {
-            thumbnail: Thumbnail::new(rgba),
+impl ThumbnailSize {
+    pub const ALL: [Self; 3] = [Self::Small, Self::Medium, Self::Large];
+
+    pub fn pixels(self) -> u32 {
+        match self {
+            Self::Small => 180,
+            Self::Medium => 360,
+            Self::Large => 540,
         }
     }
+}
+
+impl fmt::Display for ThumbnailSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Small => "Small",
+            Self::Medium => "Medium",
+            Self::Large => "Large",
+        })
+    }
+}
+
+/// How thumbnails fill their square card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThumbnailFit {
+    #[default]
+    Contain,
+    Cover,
+    Fill,
+}
+
+impl ThumbnailFit {
+    pub const ALL: [Self; 3] = [Self::Contain, Self::Cover, Self::Fill];
+
+    pub fn content_fit(self) -> ContentFit {
+        match self {
+            Self::Contain => ContentFit::Contain,
+            Self::Cover => ContentFit::Cover,
+            Self::Fill => ContentFit::Fill,
+        }
+    }
+}
 
-    pub fn load(self, rgba: Rgba) -> Self {
+impl fmt::Display for ThumbnailFit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Contain => "Contain",
+            Self::Cover => "Cover",
+            Self::Fill => "Fill",
+        })
+    }
+}
+
+impl Preview {
+    pub fn ready(
+        rgba: Rgba,
+        speed: AnimationSpeed,
+        reduce_motion: bool,
+    ) -> Self {
         Self::Ready {
-            thumbnail: Thumbnail::new(rgba),
+            thumbnail: Thumbnail::new(rgba, speed, reduce_motion),
+        }
+    }
+
+    pub fn failed(reason: Error) -> Self {
+        Self::Failed { reason }
+    }
+
+    /// The approximate memory held by the thumbnail, if it is loaded.
+    pub fn bytes(&self) -> usize {
+        match self {
+            Self::Ready { thumbnail } => thumbnail.bytes,
+            Self::Loading | Self::Failed { .. } | Self::Incomplete => 0,
         }
     }
 
-    pub fn toggle_zoom(&mut self, enabled: bool) {
+    /// Zooms the thumbnail in while hovered, unless motion is reduced.
+    pub fn toggle_zoom(&mut self, enabled: bool, reduce_motion: bool) {
         if let Self::Ready { thumbnail, .. } = self {
-            thumbnail.zoom.go_mut(enabled);
+            thumbnail.zoom.go_mut(enabled && !reduce_motion);
         }
     }
 
@@ -105,22 +334,35 @@ impl Preview {
                 thumbnail.fade_in.is_animating(now)
                     || thumbnail.zoom.is_animating(now)
             }
-            Self::Loading => false,
+            Self::Loading | Self::Failed { .. } | Self::Incomplete => false,
         }
     }
 }
 
 impl Thumbnail {
-    pub fn new(rgba: Rgba) -> Self {
+    /// Creates a thumbnail fading in at `speed`. Reducing motion keeps the
+    /// fade but makes any zoom instant.
+    pub fn new(
+        rgba: Rgba,
+        speed: AnimationSpeed,
+        reduce_motion: bool,
+    ) -> Self {
+        let zoom_duration = if reduce_motion {
+            AnimationSpeed::Off.duration()
+        } else {
+            speed.duration()
+        };
+
         Self {
+            bytes: rgba.width as usize * rgba.height as usize * 4,
             handle: image::Handle::from_rgba(
                 rgba.width,
                 rgba.height,
                 rgba.pixels,
             ),
-            fade_in: Animation::new(false).quick().go(true),
+            fade_in: Animation::new(false).duration(speed.duration()).go(true),
             zoom: Animation::new(false)
-                .quick()
+                .duration(zoom_duration)
                 .easing(animation::Easing::EaseInOut),
         }
     }
@@ -0,0 +1,74 @@
+use crate::core::rename::TemplateError;
+use iced::widget::{
+    button, center, column, container, horizontal_space, opaque, row,
+    scrollable, text, text_input,
+};
+use iced::{Center, Element, Fill};
+use iced::color;
+
+use crate::ui::gallery::Message;
+
+const WIDTH: u32 = 480;
+const PREVIEW_HEIGHT: u32 = 240;
+
+/// A modal asking for the template the selected images are renamed with,
+/// previewing the resulting names as `(old, new)` pairs.
+pub fn rename_dialog<'a>(
+    template: &'a str,
+    names: Result<Vec<(String, String)>, TemplateError>,
+    is_renaming: bool,
+) -> Element<'a, Message> {
+    let is_valid = names.is_ok();
+
+    let preview: Element<'_, _> = match names {
+        Ok(names) => scrollable(
+            column(names.into_iter().map(|(old, new)| {
+                row![text(old).width(Fill), text("→"), text(new).width(Fill)]
+                    .spacing(10)
+                    .into()
+            }))
+            .spacing(5),
+        )
+        .height(PREVIEW_HEIGHT)
+        .into(),
+        Err(error) => text(format!("Invalid pattern: {error}")).into(),
+    };
+
+    let dialog = column![
+        text("Rename selected images").size(20),
+        text_input("trip_{index:03}", template)
+            .on_input(Message::RenameTemplateChanged)
+            .on_submit(Message::ConfirmRename),
+        text(
+            "{index} counts from 1, {index:03} pads it to three digits and \
+             {name} keeps the original name.",
+        )
+        .size(12),
+        preview,
+        row![
+            horizontal_space(),
+            button(text("Cancel"))
+                .on_press(Message::CancelRename)
+                .style(button::secondary),
+            button(text(if is_renaming { "Renaming…" } else { "Rename" }))
+                .on_press_maybe(
+                    (is_valid && !is_renaming).then_some(Message::ConfirmRename),
+                ),
+        ]
+        .spacing(10)
+        .align_y(Center),
+    ]
+    .spacing(10);
+
+    opaque(
+        center(
+            container(dialog)
+                .padding(20)
+                .max_width(WIDTH)
+                .style(container::rounded_box),
+        )
+        .style(|_theme| {
+            container::Style::default().background(color!(0x000000, 0.5))
+        }),
+    )
+}
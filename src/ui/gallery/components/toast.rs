@@ -0,0 +1,69 @@
+use iced::widget::{button, container, row, text};
+use iced::{border, Center, Element, Fill, Theme};
+
+use crate::ui::gallery::Message;
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    message: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Info,
+    Error,
+}
+
+impl Toast {
+    const MAX_WIDTH: u32 = 480;
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: Kind::Info,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: Kind::Error,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let toast = container(
+            row![
+                text(&self.message).width(Fill),
+                button(text("Dismiss"))
+                    .on_press(Message::DismissToast)
+                    .style(button::text),
+            ]
+            .spacing(10)
+            .align_y(Center),
+        )
+        .padding(10)
+        .max_width(Self::MAX_WIDTH)
+        .style(move |theme: &Theme| {
+            let palette = theme.extended_palette();
+            let pair = match self.kind {
+                Kind::Info => palette.background.strong,
+                Kind::Error => palette.danger.base,
+            };
+
+            container::Style {
+                background: Some(pair.color.into()),
+                text_color: Some(pair.text),
+                border: border::rounded(4),
+                ..container::Style::default()
+            }
+        });
+
+        container(toast)
+            .center_x(Fill)
+            .align_bottom(Fill)
+            .padding(20)
+            .into()
+    }
+}
@@ -1,49 +1,235 @@
-use crate::core::{Id, Rgba};
+use crate::core::{Error, Histogram, Id, Metadata, Rgba};
+use crate::ui::gallery::components::checkerboard::Checkerboard;
+use crate::ui::gallery::components::histogram;
 use iced::animation;
+use iced::mouse;
 use iced::time::Instant;
-use iced::widget::{container, horizontal_space, image, mouse_area, opaque};
-use iced::{Element, Fill, Theme, Animation};
-use iced::color;
+use iced::widget::{
+    button, canvas, column, container, horizontal_space, image, mouse_area,
+    opaque, row, scrollable, stack, text,
+};
+use iced::widget::scrollable::AbsoluteOffset;
+use iced::{Border, ContentFit, Element, Fill, Theme, Animation};
+use iced::{Padding, Point, Size, Task, Vector};
+use iced::{border, color};
 
-use crate::ui::gallery::Message;
+use crate::ui::gallery::{AnimationSpeed, Message};
+
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+
+static FILMSTRIP: LazyLock<scrollable::Id> =
+    LazyLock::new(scrollable::Id::unique);
 
 pub struct Viewer {
     image: Option<image::Handle>,
+    /// The image shown before the current one, fading out while the
+    /// current one fades in over it.
+    previous_image: Option<image::Handle>,
+    /// The image shown next to the current one while comparing them.
+    compared: Option<image::Handle>,
+    compared_id: Option<Id>,
+    original: Option<Rgba>,
+    quarter_turns: u8,
     background_fade_in: Animation<bool>,
     image_fade_in: Animation<bool>,
+    crossfade: Animation<bool>,
+    loading_pulse: Animation<bool>,
     current_id: Option<Id>,
     current_index: Option<usize>,
+    has_failed: bool,
+    preloaded: VecDeque<(Id, Rgba)>,
+    zoom: f32,
+    pan_offset: Vector,
+    viewport: Size,
+    cursor: Point,
+    drag_origin: Option<(Point, Vector)>,
+    /// The EXIF of the current image, or why it could not be read.
+    metadata: Option<Result<Metadata, Error>>,
+    is_info_visible: bool,
+    is_caption_visible: bool,
+    is_filmstrip_visible: bool,
+    mode: ViewMode,
+    background: Background,
+    /// The histogram of the current image, or why it could not be
+    /// computed.
+    histogram: Option<Result<Histogram, Error>>,
+    histogram_cache: canvas::Cache,
+    is_histogram_visible: bool,
+    animation_speed: AnimationSpeed,
+    reduce_motion: bool,
+}
+
+/// How the viewer scales the current image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    /// Scaled down to fit the window.
+    #[default]
+    Fit,
+    /// At its native resolution, scrolling when it overflows the window.
+    Actual,
+}
+
+/// What the viewer shows behind the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Background {
+    #[default]
+    Black,
+    Grey,
+    White,
+    /// Reveals the transparent regions of the image.
+    Checkerboard,
+}
+
+impl Background {
+    fn next(self) -> Self {
+        match self {
+            Self::Black => Self::Grey,
+            Self::Grey => Self::White,
+            Self::White => Self::Checkerboard,
+            Self::Checkerboard => Self::Black,
+        }
+    }
 }
 
 impl Viewer {
+    /// The maximum amount of originals kept around for instant navigation:
+    /// the current image and its two neighbors.
+    pub const PRELOAD_LIMIT: usize = 3;
+
+    const SPINNER_SIZE: u32 = 48;
+    const PADDING: f32 = 20.0;
+
+    const MIN_ZOOM: f32 = 1.0;
+    const MAX_ZOOM: f32 = 8.0;
+    const ZOOM_STEP: f32 = 1.1;
+    const PIXELS_PER_LINE: f32 = 40.0;
+
+    const HISTOGRAM_WIDTH: u32 = 256;
+    const HISTOGRAM_HEIGHT: u32 = 100;
+
+    const FILMSTRIP_SIZE: f32 = 64.0;
+    const FILMSTRIP_SPACING: f32 = 4.0;
+
     pub fn new() -> Self {
         Self {
             image: None,
+            previous_image: None,
+            compared: None,
+            compared_id: None,
+            original: None,
+            quarter_turns: 0,
             background_fade_in: Animation::new(false)
                 .quick()
                 .easing(animation::Easing::EaseInOut),
             image_fade_in: Animation::new(false)
                 .quick()
                 .easing(animation::Easing::EaseInOut),
+            crossfade: Animation::new(true)
+                .quick()
+                .easing(animation::Easing::EaseInOut),
+            loading_pulse: Animation::new(false)
+                .slow()
+                .easing(animation::Easing::EaseInOut),
             current_id: None,
-            current_index: Some(0),
+            current_index: None,
+            has_failed: false,
+            preloaded: VecDeque::new(),
+            zoom: Self::MIN_ZOOM,
+            pan_offset: Vector::ZERO,
+            viewport: Size::ZERO,
+            cursor: Point::ORIGIN,
+            drag_origin: None,
+            metadata: None,
+            is_info_visible: false,
+            is_caption_visible: true,
+            is_filmstrip_visible: true,
+            mode: ViewMode::default(),
+            background: Background::default(),
+            histogram: None,
+            histogram_cache: canvas::Cache::new(),
+            is_histogram_visible: false,
+            animation_speed: AnimationSpeed::default(),
+            reduce_motion: false,
         }
     }
 
+    /// Changes how long the fades take. The loading indicator pulses at half
+    /// that pace and stands still when animations are off.
+    pub fn set_animation_speed(&mut self, speed: AnimationSpeed) {
+        let duration = speed.duration();
+
+        self.background_fade_in =
+            self.background_fade_in.clone().duration(duration);
+        self.image_fade_in = self.image_fade_in.clone().duration(duration);
+        self.crossfade = self.crossfade.clone().duration(duration);
+        self.loading_pulse = self.loading_pulse.clone().duration(duration * 2);
+        self.animation_speed = speed;
+    }
+
+    /// Shows images without growing or shrinking them as they fade in.
+    pub fn set_reduce_motion(&mut self, reduce_motion: bool) {
+        self.reduce_motion = reduce_motion;
+    }
+
     pub fn current_id(&self) -> Option<Id> {
         self.current_id.clone()
     }
 
-    pub fn set_current_id(&mut self, id: Option<Id>) {
-        self.current_id = id;
+    /// Moves to the image `id` at `index` of the gallery, forgetting the
+    /// state of the previous image.
+    pub fn go_to(&mut self, index: usize, id: Id) {
+        self.current_index = Some(index);
+        self.current_id = Some(id);
+        self.compared = None;
+        self.compared_id = None;
+        self.has_failed = false;
+        self.quarter_turns = 0;
+        self.metadata = None;
+        self.histogram = None;
+        self.histogram_cache.clear();
+        self.reset_zoom();
     }
 
     pub fn current_index(&self) -> Option<usize> {
         self.current_index
     }
 
-    pub fn set_current_index(&mut self, index: Option<usize>) {
-        self.current_index = index;
+    /// The index and id of the current image.
+    pub fn current(&self) -> Option<(usize, Id)> {
+        self.current_index.zip(self.current_id)
+    }
+
+    /// Updates the index of the current image after the images around it
+    /// were removed or reordered.
+    pub fn reindex(&mut self, index: usize) {
+        if self.current_id.is_some() {
+            self.current_index = Some(index);
+        }
+    }
+
+    /// Shows the image at `index` side by side with the image `compared`,
+    /// zooming and panning both together.
+    pub fn compare(&mut self, index: usize, id: Id, compared: Id) {
+        self.go_to(index, id);
+        self.compared_id = Some(compared);
+    }
+
+    pub fn compared_id(&self) -> Option<Id> {
+        self.compared_id
+    }
+
+    pub fn is_comparing(&self) -> bool {
+        self.compared_id.is_some()
+    }
+
+    pub fn show_compared(&mut self, rgba: Rgba) {
+        self.compared = Some(image::Handle::from_rgba(
+            rgba.width,
+            rgba.height,
+            rgba.pixels,
+        ));
+        self.has_failed = false;
     }
 
     pub fn is_open(&self, now: Instant) -> bool {
@@ -52,59 +238,684 @@ impl Viewer {
 
     pub fn open(&mut self) {
         self.image = None;
+        self.previous_image = None;
+        self.compared = None;
+        self.compared_id = None;
+        self.original = None;
+        self.quarter_turns = 0;
+        self.has_failed = false;
+        self.reset_zoom();
         self.background_fade_in.go_mut(true);
     }
 
     pub fn show(&mut self, rgba: Rgba) {
+        if let Some(previous) = self.image.take() {
+            self.previous_image = Some(previous);
+            self.crossfade = Animation::new(false)
+                .duration(self.animation_speed.duration())
+                .easing(animation::Easing::EaseInOut);
+            self.crossfade.go_mut(true);
+        }
+
         self.image = Some(image::Handle::from_rgba(
             rgba.width,
             rgba.height,
-            rgba.pixels,
+            rgba.pixels.clone(),
         ));
+        self.original = Some(rgba);
+        self.quarter_turns = 0;
+        self.has_failed = false;
         self.background_fade_in.go_mut(true);
         self.image_fade_in.go_mut(true);
     }
 
+    /// Rotates the displayed image by a quarter turn, without touching the
+    /// file until the rotation is saved.
+    pub fn rotate(&mut self, clockwise: bool) {
+        let Some(original) = &self.original else {
+            return;
+        };
+
+        let rotated = original.rotate(clockwise);
+
+        self.image = Some(image::Handle::from_rgba(
+            rotated.width,
+            rotated.height,
+            rotated.pixels.clone(),
+        ));
+        self.original = Some(rotated);
+        self.quarter_turns =
+            (self.quarter_turns + if clockwise { 1 } else { 3 }) % 4;
+    }
+
+    /// The pixels of the image being shown, including any pending rotation.
+    pub fn current_image(&self) -> Option<Rgba> {
+        self.original.clone()
+    }
+
+    /// The clockwise quarter turns applied since the image was shown.
+    pub fn pending_rotation(&self) -> Option<u8> {
+        (self.quarter_turns != 0).then_some(self.quarter_turns)
+    }
+
+    /// Marks the current rotation as saved, returning the rotated pixels.
+    pub fn confirm_rotation(&mut self) -> Option<Rgba> {
+        self.quarter_turns = 0;
+        self.original.clone()
+    }
+
     pub fn close(&mut self) {
         self.background_fade_in.go_mut(false);
         self.image_fade_in.go_mut(false);
         self.current_id = None;
+        self.current_index = None;
+        self.previous_image = None;
+        self.compared = None;
+        self.compared_id = None;
+        self.original = None;
+        self.quarter_turns = 0;
+        self.preloaded.clear();
+        self.reset_zoom();
+    }
+
+    pub fn set_metadata(&mut self, metadata: Result<Metadata, Error>) {
+        self.metadata = Some(metadata);
+    }
+
+    pub fn toggle_info(&mut self) {
+        self.is_info_visible = !self.is_info_visible;
+    }
+
+    pub fn set_histogram(&mut self, histogram: Result<Histogram, Error>) {
+        self.histogram = Some(histogram);
+        self.histogram_cache.clear();
+    }
+
+    pub fn toggle_histogram(&mut self) {
+        self.is_histogram_visible = !self.is_histogram_visible;
+    }
+
+    pub fn toggle_caption(&mut self) {
+        self.is_caption_visible = !self.is_caption_visible;
+    }
+
+    pub fn toggle_filmstrip(&mut self) {
+        self.is_filmstrip_visible = !self.is_filmstrip_visible;
+    }
+
+    /// Switches between fitting the image to the window and showing it at
+    /// its actual size.
+    pub fn toggle_mode(&mut self) {
+        if self.is_comparing() {
+            return;
+        }
+
+        self.mode = match self.mode {
+            ViewMode::Fit => ViewMode::Actual,
+            ViewMode::Actual => ViewMode::Fit,
+        };
+        self.reset_zoom();
+    }
+
+    /// Switches to the next backdrop, from black to grey, white and a
+    /// checkerboard.
+    pub fn cycle_background(&mut self) {
+        self.background = self.background.next();
+    }
+
+    /// Whether the image is shown at its native resolution.
+    pub fn is_actual_size(&self) -> bool {
+        self.mode() == ViewMode::Actual
+    }
+
+    pub fn resize(&mut self, viewport: Size) {
+        self.viewport = viewport;
+        self.pan_offset = self.clamp_pan(self.pan_offset);
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom > Self::MIN_ZOOM
+    }
+
+    pub fn zoom(&mut self, delta: mouse::ScrollDelta) {
+        // The wheel scrolls the image instead
+        if self.mode() == ViewMode::Actual {
+            return;
+        }
+
+        let lines = match delta {
+            mouse::ScrollDelta::Lines { y, .. } => y,
+            mouse::ScrollDelta::Pixels { y, .. } => y / Self::PIXELS_PER_LINE,
+        };
+
+        self.zoom = (self.zoom * Self::ZOOM_STEP.powf(lines))
+            .clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        self.pan_offset = self.clamp_pan(self.pan_offset);
+    }
+
+    pub fn start_drag(&mut self) {
+        if self.is_zoomed() {
+            self.drag_origin = Some((self.cursor, self.pan_offset));
+        }
+    }
+
+    pub fn move_cursor(&mut self, position: Point) {
+        self.cursor = position;
+
+        if let Some((origin, pan_offset)) = self.drag_origin {
+            self.pan_offset = self.clamp_pan(pan_offset + (position - origin));
+        }
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag_origin = None;
+    }
+
+    /// Compared images are always fit to their half of the window.
+    fn mode(&self) -> ViewMode {
+        if self.is_comparing() {
+            ViewMode::Fit
+        } else {
+            self.mode
+        }
+    }
+
+    /// The area each image is shown in, which is half the window while
+    /// comparing.
+    fn pane(&self) -> Size {
+        if self.is_comparing() {
+            Size::new(self.viewport.width / 2.0, self.viewport.height)
+        } else {
+            self.viewport
+        }
+    }
+
+    fn reset_zoom(&mut self) {
+        self.zoom = Self::MIN_ZOOM;
+        self.pan_offset = Vector::ZERO;
+        self.drag_origin = None;
+    }
+
+    /// The amount the zoomed image overflows its pane on each axis.
+    fn overflow(&self) -> Vector {
+        let pane = self.pane();
+        let width = (pane.width - 2.0 * Self::PADDING).max(0.0);
+        let height = (pane.height - 2.0 * Self::PADDING).max(0.0);
+
+        Vector::new(width, height) * (self.zoom - 1.0)
+    }
+
+    fn clamp_pan(&self, pan_offset: Vector) -> Vector {
+        let limit = self.overflow() * 0.5;
+
+        Vector::new(
+            pan_offset.x.clamp(-limit.x, limit.x),
+            pan_offset.y.clamp(-limit.y, limit.y),
+        )
+    }
+
+    /// Grows the image area by the zoom factor and shifts it by the pan
+    /// offset. Padding may turn negative so the area can overflow the
+    /// viewport.
+    fn padding(&self) -> Padding {
+        let overflow = self.overflow() * 0.5;
+
+        Padding {
+            top: Self::PADDING - overflow.y + self.pan_offset.y,
+            right: Self::PADDING - overflow.x - self.pan_offset.x,
+            bottom: Self::PADDING - overflow.y - self.pan_offset.y,
+            left: Self::PADDING - overflow.x + self.pan_offset.x,
+        }
+    }
+
+    /// The memory held by the full image shown, if any.
+    pub fn image_bytes(&self) -> Option<usize> {
+        self.original.as_ref().map(|rgba| rgba.pixels.len())
+    }
+
+    /// The memory held by the images decoded ahead of time, besides the one
+    /// shown.
+    pub fn preloaded_bytes(&self) -> usize {
+        self.preloaded
+            .iter()
+            .filter(|(id, _)| Some(*id) != self.current_id)
+            .map(|(_, rgba)| rgba.pixels.len())
+            .sum()
+    }
+
+    pub fn is_preloaded(&self, id: Id) -> bool {
+        self.preloaded.iter().any(|(candidate, _)| *candidate == id)
+    }
+
+    pub fn preloaded(&mut self, id: Id) -> Option<Rgba> {
+        let position = self
+            .preloaded
+            .iter()
+            .position(|(candidate, _)| *candidate == id)?;
+
+        let entry = self.preloaded.remove(position)?;
+        let rgba = entry.1.clone();
+        self.preloaded.push_back(entry);
+
+        Some(rgba)
+    }
+
+    pub fn preload(&mut self, id: Id, rgba: Rgba) {
+        self.preloaded.retain(|(candidate, _)| *candidate != id);
+        self.preloaded.push_back((id, rgba));
+
+        while self.preloaded.len() > Self::PRELOAD_LIMIT {
+            let _ = self.preloaded.pop_front();
+        }
+    }
+
+    pub fn retain_preloaded(&mut self, f: impl Fn(Id) -> bool) {
+        self.preloaded.retain(|(id, _)| f(*id));
+    }
+
+    /// Stops waiting for the current original after its download failed.
+    pub fn fail(&mut self) {
+        self.has_failed = true;
+    }
+
+    /// Whether an image has been opened but its original, or the one it is
+    /// compared to, is not ready yet.
+    pub fn is_loading(&self) -> bool {
+        let is_pending = self.image.is_none()
+            || (self.compared_id.is_some() && self.compared.is_none());
+
+        self.current_id.is_some() && is_pending && !self.has_failed
+    }
+
+    /// Keeps the loading indicator pulsing back and forth until the image
+    /// arrives.
+    pub fn tick(&mut self, now: Instant) {
+        if self.is_loading()
+            && self.animation_speed != AnimationSpeed::Off
+            && !self.loading_pulse.is_animating(now)
+        {
+            let is_expanded =
+                self.loading_pulse.interpolate(0.0, 1.0, now) >= 1.0;
+
+            self.loading_pulse.go_mut(!is_expanded);
+        }
+
+        if self.previous_image.is_some() && !self.crossfade.is_animating(now) {
+            self.previous_image = None;
+        }
     }
 
     pub fn is_animating(&self, now: Instant) -> bool {
         self.background_fade_in.is_animating(now)
             || self.image_fade_in.is_animating(now)
+            || self.crossfade.is_animating(now)
+            || (self.is_loading()
+                && self.animation_speed != AnimationSpeed::Off)
     }
 
-    pub fn view(&self, now: Instant) -> Element<'_, Message> {
+    /// Renders the viewer overlay, with an optional `caption` describing the
+    /// current image and a `filmstrip` of the images around it, with their
+    /// thumbnails if decoded, along its bottom edge.
+    pub fn view<'a>(
+        &'a self,
+        now: Instant,
+        caption: Option<String>,
+        filmstrip: Vec<(Id, Option<&'a image::Handle>)>,
+    ) -> Element<'a, Message> {
         let opacity = self.background_fade_in.interpolate(0.0, 0.8, now);
 
-        let image: Element<'_, _> = if let Some(handle) = &self.image {
-            image(handle)
-                .width(Fill)
-                .height(Fill)
-                .opacity(self.image_fade_in.interpolate(0.0, 1.0, now))
-                .scale(self.image_fade_in.interpolate(1.5, 1.0, now))
+        if opacity > 0.0 {
+            let content: Element<'_, _> = if self.is_comparing() {
+                row![
+                    self.pane_view(self.image.as_ref(), None, now),
+                    self.pane_view(self.compared.as_ref(), None, now),
+                ]
+                .into()
+            } else {
+                self.pane_view(
+                    self.image.as_ref(),
+                    self.previous_image.as_ref(),
+                    now,
+                )
+            };
+
+            let background = self.background;
+
+            let content: Element<'_, _> = match background {
+                Background::Checkerboard => stack![
+                    canvas(Checkerboard { opacity }).width(Fill).height(Fill),
+                    content,
+                ]
+                .into(),
+                _ => content,
+            };
+
+            let overlay = mouse_area(
+                container(content)
+                    .width(Fill)
+                    .height(Fill)
+                    .style(move |_theme| {
+                        let color = match background {
+                            Background::Black => color!(0x000000, opacity),
+                            Background::Grey => color!(0x808080, opacity),
+                            Background::White => color!(0xffffff, opacity),
+                            Background::Checkerboard => {
+                                color!(0x666666, opacity)
+                            }
+                        };
+
+                        container::Style::default().background(color)
+                    }),
+            );
+
+            let is_inspecting =
+                self.is_zoomed() || self.mode() == ViewMode::Actual;
+
+            // Presses pan a zoomed image or grab the scrollbars of an image
+            // at its actual size instead of closing the viewer
+            let overlay = opaque(if is_inspecting {
+                overlay
+            } else {
+                overlay.on_press(Message::Close)
+            });
+
+            let info = self.is_info_visible.then(|| self.info(opacity));
+
+            let histogram = self
+                .histogram
+                .as_ref()
+                .filter(|_| self.is_histogram_visible)
+                .map(|histogram| self.histogram(histogram, opacity));
+
+            let filmstrip = (self.is_filmstrip_visible
+                && !self.is_comparing()
+                && !filmstrip.is_empty())
+            .then(|| self.filmstrip(filmstrip, opacity));
+
+            // The caption floats above the filmstrip
+            let lift = if filmstrip.is_some() {
+                Self::FILMSTRIP_SIZE + 2.0 * Self::FILMSTRIP_SPACING
+            } else {
+                0.0
+            };
+
+            let caption = caption
+                .filter(|_| self.is_caption_visible)
+                .map(|caption| self.caption(caption, opacity, lift));
+
+            stack![overlay]
+                .push_maybe(info)
+                .push_maybe(histogram)
+                .push_maybe(filmstrip)
+                .push_maybe(caption)
                 .into()
         } else {
             horizontal_space().into()
-        };
+        }
+    }
 
-        if opacity > 0.0 {
-            opaque(
-                mouse_area(
-                    container(image)
-                        .center(Fill)
-                        .style(move |_theme| {
-                            container::Style::default()
-                                .background(color!(0x000000, opacity))
-                        })
-                        .padding(20),
-                )
-                .on_press(Message::Close),
+    /// Renders an image in its own pane, so compared images are zoomed and
+    /// panned alike. A `previous` image fades out behind it.
+    fn pane_view<'a>(
+        &'a self,
+        handle: Option<&'a image::Handle>,
+        previous: Option<&'a image::Handle>,
+        now: Instant,
+    ) -> Element<'a, Message> {
+        let image: Element<'_, _> = match (handle, self.mode()) {
+            (Some(handle), ViewMode::Fit) => {
+                let fade_in = self.image_fade_in.interpolate(0.0, 1.0, now);
+                let crossfade = self.crossfade.interpolate(0.0, 1.0, now);
+
+                let scale = if self.reduce_motion {
+                    1.0
+                } else {
+                    self.image_fade_in.interpolate(1.5, 1.0, now)
+                };
+
+                let current = image(handle)
+                    .width(Fill)
+                    .height(Fill)
+                    .opacity(fade_in * crossfade)
+                    .scale(scale);
+
+                match previous {
+                    Some(previous) => stack![
+                        image(previous)
+                            .width(Fill)
+                            .height(Fill)
+                            .opacity(fade_in * (1.0 - crossfade)),
+                        current,
+                    ]
+                    .into(),
+                    None => current.into(),
+                }
+            }
+            (Some(handle), ViewMode::Actual) => scrollable(
+                image(handle)
+                    .opacity(self.image_fade_in.interpolate(0.0, 1.0, now)),
             )
+            .direction(scrollable::Direction::Both {
+                vertical: scrollable::Scrollbar::default(),
+                horizontal: scrollable::Scrollbar::default(),
+            })
+            .into(),
+            (None, _) => self.placeholder(now),
+        };
+
+        container(image)
+            .center(Fill)
+            .padding(self.padding())
+            .clip(true)
+            .into()
+    }
+
+    /// The pulsing spinner shown while the original is loading.
+    fn placeholder<'a>(&self, now: Instant) -> Element<'a, Message> {
+        if self.is_loading() {
+            let pulse = self.loading_pulse.interpolate(0.2, 0.9, now);
+
+            container(horizontal_space())
+                .width(Self::SPINNER_SIZE)
+                .height(Self::SPINNER_SIZE)
+                .style(move |_theme| container::Style {
+                    background: Some(color!(0xffffff, pulse).into()),
+                    border: border::rounded(Self::SPINNER_SIZE / 2),
+                    ..container::Style::default()
+                })
+                .into()
         } else {
             horizontal_space().into()
         }
     }
-} 
\ No newline at end of file
+
+    fn caption<'a>(
+        &self,
+        caption: String,
+        opacity: f32,
+        lift: f32,
+    ) -> Element<'a, Message> {
+        let text_opacity = opacity / 0.8;
+
+        container(
+            container(text(caption))
+                .padding([5, 10])
+                .style(move |_theme| container::Style {
+                    background: Some(color!(0x000000, opacity).into()),
+                    text_color: Some(color!(0xffffff, text_opacity)),
+                    border: border::rounded(4),
+                    ..container::Style::default()
+                }),
+        )
+        .center_x(Fill)
+        .align_bottom(Fill)
+        .padding(Padding {
+            bottom: Self::PADDING + lift,
+            ..Padding::new(Self::PADDING)
+        })
+        .into()
+    }
+
+    /// Scrolls the filmstrip so the thumbnail at `position`, out of `count`,
+    /// sits in its middle within a window `width` wide.
+    pub fn center_filmstrip<T>(
+        position: usize,
+        count: usize,
+        width: f32,
+    ) -> Task<T> {
+        let step = Self::FILMSTRIP_SIZE + Self::FILMSTRIP_SPACING;
+        let content = count as f32 * step - Self::FILMSTRIP_SPACING;
+        let visible =
+            (width - 2.0 * Self::FILMSTRIP_SPACING).clamp(0.0, content);
+        let center = position as f32 * step + Self::FILMSTRIP_SIZE / 2.0;
+        let x = (center - visible / 2.0).clamp(0.0, content - visible);
+
+        scrollable::scroll_to(FILMSTRIP.clone(), AbsoluteOffset { x, y: 0.0 })
+    }
+
+    /// A strip of thumbnails along the bottom, opening the image of the
+    /// one pressed. Thumbnails still decoding are left blank.
+    fn filmstrip<'a>(
+        &self,
+        items: Vec<(Id, Option<&'a image::Handle>)>,
+        opacity: f32,
+    ) -> Element<'a, Message> {
+        let current_id = self.current_id;
+
+        let thumbnails = row(items.into_iter().map(|(id, handle)| {
+            let thumbnail: Element<'_, _> = match handle {
+                Some(handle) => image(handle)
+                    .width(Fill)
+                    .height(Fill)
+                    .content_fit(ContentFit::Cover)
+                    .into(),
+                None => horizontal_space().into(),
+            };
+
+            let is_current = Some(id) == current_id;
+
+            button(thumbnail)
+                .on_press(Message::Open(id))
+                .width(Self::FILMSTRIP_SIZE)
+                .height(Self::FILMSTRIP_SIZE)
+                .padding(2)
+                .style(move |theme: &Theme, _status| {
+                    let border = if is_current {
+                        theme.palette().primary
+                    } else {
+                        color!(0x000000, 0.0)
+                    };
+
+                    button::Style {
+                        background: Some(color!(0x202020, opacity).into()),
+                        border: Border {
+                            color: border,
+                            width: 2.0,
+                            radius: 2.0.into(),
+                        },
+                        ..button::Style::default()
+                    }
+                })
+                .into()
+        }))
+        .spacing(Self::FILMSTRIP_SPACING);
+
+        container(
+            scrollable(thumbnails)
+                .id(FILMSTRIP.clone())
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new().width(4).scroller_width(4),
+                )),
+        )
+        .center_x(Fill)
+        .align_bottom(Fill)
+        .padding(Self::FILMSTRIP_SPACING)
+        .into()
+    }
+
+    /// The histogram in the bottom left corner, or a note in its place
+    /// when it could not be computed.
+    fn histogram<'a>(
+        &'a self,
+        histogram: &'a Result<Histogram, Error>,
+        opacity: f32,
+    ) -> Element<'a, Message> {
+        let content: Element<'_, _> = match histogram {
+            Ok(histogram) => canvas(histogram::Chart {
+                histogram,
+                cache: &self.histogram_cache,
+            })
+            .width(Self::HISTOGRAM_WIDTH)
+            .height(Self::HISTOGRAM_HEIGHT)
+            .into(),
+            Err(_) => text("Histogram unavailable").size(14).into(),
+        };
+
+        container(
+            container(content)
+                .padding(10)
+                .style(move |_theme| container::Style {
+                    background: Some(color!(0x000000, opacity).into()),
+                    border: border::rounded(4),
+                    ..container::Style::default()
+                }),
+        )
+        .align_left(Fill)
+        .align_bottom(Fill)
+        .padding(Self::PADDING)
+        .into()
+    }
+
+    fn info(&self, opacity: f32) -> Element<'_, Message> {
+        let content: Element<'_, _> = match &self.metadata {
+            Some(Ok(metadata)) if metadata.is_empty() => {
+                text("No metadata available.").into()
+            }
+            Some(Ok(metadata)) => {
+                let location = metadata.location.map(|location| {
+                    row![
+                        text("Location").width(110),
+                        column![
+                            text(location.to_string()),
+                            button(text("Open in maps"))
+                                .on_press(Message::OpenInMaps(location))
+                                .padding(0)
+                                .style(button::text),
+                        ]
+                        .spacing(5),
+                    ]
+                    .spacing(10)
+                });
+
+                column(metadata.entries().map(|(label, value)| {
+                    row![text(label).width(110), text(value)]
+                        .spacing(10)
+                        .into()
+                }))
+                .push_maybe(location)
+                .spacing(5)
+                .into()
+            }
+            Some(Err(error)) => {
+                text(format!("Failed to read metadata: {error}")).into()
+            }
+            None => text("Loading metadata...").into(),
+        };
+
+        container(
+            container(content)
+                .padding(10)
+                .max_width(360)
+                .style(move |_theme| container::Style {
+                    background: Some(color!(0x000000, opacity).into()),
+                    text_color: Some(color!(0xffffff)),
+                    border: border::rounded(4),
+                    ..container::Style::default()
+                }),
+        )
+        .align_right(Fill)
+        .align_top(Fill)
+        .padding(Self::PADDING)
+        .into()
+    }
+}
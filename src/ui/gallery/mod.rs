@@ -1,140 +1,1183 @@
-use crate::core::{Error, Id, ImageData, Rgba, Size};
+use crate::core::{
+    Details, Diff, Error, ExportFormat, Histogram, Id, Ids, ImageData,
+    Location, Metadata, ResizeFilter, Rgba, Size, is_archive, is_url_list,
+};
+use crate::core::favorites::{self, Favorites};
+use crate::core::ratings::{self, Ratings};
+use crate::core::rename::{self, TemplateError};
+use crate::core::settings::Settings;
 use crate::core::helper;
-use crate::ui::gallery::components::{Preview, Viewer, card, placeholder};
+use crate::ui::gallery::components::{
+    Preview, Toast, Viewer, card, context_menu, help, placeholder,
+    rename_dialog,
+};
 
 mod components;
 
+pub use components::{ThumbnailFit, ThumbnailSize};
+
 use iced::animation;
-use iced::time::Instant;
-use iced::widget::{center_x, container, row, scrollable, stack};
+use iced::futures::future;
+use iced::time::{self, Instant};
+use iced::widget::{
+    button, center, center_x, checkbox, column, container, horizontal_space,
+    keyed_column, pick_list, progress_bar, row, scrollable, slider, stack,
+    text, text_input, vertical_space,
+};
+use iced::mouse;
 use iced::window;
-use iced::{Animation, Element, Subscription, Task, Theme};
-use iced::widget::scrollable::Viewport;
-use iced::keyboard::Event;
+use iced::{
+    Animation, Border, Center, Element, Fill, Padding, Point, Rectangle,
+    Subscription, Task, Theme,
+};
+use iced::widget::scrollable::{AbsoluteOffset, RelativeOffset, Viewport};
+use iced::keyboard::{self, Event};
 use iced::keyboard::key::Key;
 use iced::keyboard::key::Named;
 use iced::event::{self, Event as IcedEvent};
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+static GRID: LazyLock<scrollable::Id> = LazyLock::new(scrollable::Id::unique);
 
+/// A grid of the images in a set of folders, opening them in a viewer.
+///
+/// It owns no window, so it can be embedded into a larger application by
+/// forwarding its [`Message`]s to [`Gallery::update`] and mapping its
+/// [`Gallery::view`] and [`Gallery::subscription`].
 pub struct Gallery {
     images: Vec<ImageData>,
+    ids: Ids,
     previews: HashMap<Id, Preview>,
+    /// The details shown in the tooltip of each card, or why they could
+    /// not be read.
+    details: HashMap<Id, Result<Details, Error>>,
+    selected_ids: HashSet<Id>,
+    favorites: HashSet<Id>,
+    /// Every starred path, including those outside the open directories.
+    favorite_paths: Favorites,
+    favorites_only: bool,
+    /// The stars given to images, by path.
+    ratings: Ratings,
+    minimum_rating: MinimumRating,
+    show_incomplete: bool,
+    selection_anchor: Option<Id>,
+    /// The position of the card focused with the arrow keys among the
+    /// visible ones.
+    focused_index: Option<usize>,
+    last_click: Option<(Id, Instant)>,
+    modifiers: keyboard::Modifiers,
+    copy_progress: Option<CopyProgress>,
+    rename: Option<Rename>,
+    is_help_visible: bool,
+    /// Whether the timing of the decodes so far floats over the grid.
+    #[cfg(feature = "metrics")]
+    is_metrics_visible: bool,
+    thumbnails_requested: usize,
+    thumbnails_decoded: usize,
+    /// Counts the times every thumbnail was discarded, so the ones still
+    /// decoding from before are dropped when they arrive.
+    thumbnail_generation: u32,
     viewer: Viewer,
+    context_menu: Option<(Id, Point)>,
+    cursor: Point,
+    toast: Option<Toast>,
+    thumbnail_size: ThumbnailSize,
+    thumbnail_fit: ThumbnailFit,
+    resize_filter: ResizeFilter,
+    export_format: ExportFormat,
+    columns: Columns,
+    layout: Layout,
+    spacing: Spacing,
+    /// How many images PageUp and PageDown skip in the viewer.
+    page_stride: usize,
+    /// The page of the grid being shown, holding [`ImageData::LIMIT`]
+    /// images each.
+    page: usize,
+    sort_order: SortOrder,
+    /// The preferences saved for the next session.
+    settings: Settings,
+    animation_speed: AnimationSpeed,
+    /// Disables zooming and scaling animations, keeping the fades.
+    reduce_motion: bool,
+    /// The phase shared by every placeholder card while the directories are
+    /// being listed, and by the spinners of the cards still decoding.
+    shimmer: Animation<bool>,
+    /// Fades the button scrolling back to the top in once the grid is
+    /// scrolled far enough.
+    back_to_top: Animation<bool>,
+    memory_budget: MemoryBudget,
+    /// When each card was last on screen, so the thumbnails unseen for the
+    /// longest are evicted first.
+    last_seen: HashMap<Id, Instant>,
+    /// The cards within the viewport, which are never evicted.
+    on_screen: HashSet<Id>,
+    filter: String,
+    scroll_offset: RelativeOffset,
+    /// The scrolled distance and height of the grid, to keep the focused
+    /// card in view.
+    grid_viewport: Option<(f32, f32)>,
+    /// Where the grid is in the window.
+    grid_bounds: Option<Rectangle>,
+    rubber_band: Option<RubberBand>,
+    diff: Option<DiffMode>,
+    /// The topmost image on screen and when the grid was last scrolled,
+    /// floating next to the scrollbar for a moment.
+    peek: Option<(Id, Instant)>,
+    /// The offset of the grid when the viewer was opened, restored once it
+    /// closes.
+    saved_offset: Option<RelativeOffset>,
+    window_size: iced::Size,
+    /// The theme picked in the toolbar, or `None` to follow the system.
+    theme: Option<Theme>,
     now: Instant,
-    image_dir: Option<PathBuf>,
+    image_dirs: Vec<PathBuf>,
+    /// Whether the directories are scanned for new files periodically.
+    is_watching: bool,
+    /// The new files found by the last scan, listed once a later scan still
+    /// finds them so a burst of writes settles first.
+    unsettled: HashSet<PathBuf>,
+    /// The file given instead of a directory, opened in the viewer as soon
+    /// as it is listed.
+    pending_open: Option<PathBuf>,
+    /// The directories that could not be read, with the reason why.
+    unreadable_dirs: Vec<(PathBuf, Error)>,
+    /// The lowercase extensions of the files listed as images.
+    extensions: Vec<String>,
+    /// The most images listed, given on the command line.
+    limit: Option<usize>,
+    /// How many images were found when more than the limit were, and only
+    /// the first ones were listed.
+    found: Option<usize>,
+    /// The files whose thumbnail failed to decode, with the reason why.
+    failures: Vec<(PathBuf, Error)>,
+    is_failures_expanded: bool,
+    /// When the last file was dropped onto the window, since dropping many
+    /// files at once emits an event for each of them.
+    last_drop: Option<Instant>,
+    loaded: bool,
 }
 
+/// The interactions of a [`Gallery`] and the results of its background
+/// work, which are handed back to [`Gallery::update`].
 #[derive(Debug, Clone)]
 pub enum Message {
-    OpenImageDirectory(PathBuf),
-    ImagesListed(Result<Vec<ImageData>, Error>),
+    /// Replaces the browsed folders, opening any file among them.
+    OpenImageDirectories(Vec<PathBuf>),
+    /// Adds a folder to the browsed ones.
+    AddDirectory(PathBuf),
+    /// The files found in the folders opened together, listed once they
+    /// are still the browsed ones.
+    DirectoriesScanned(Vec<PathBuf>, Result<helper::Scanned, Error>),
+    /// The files found in an added folder.
+    DirectoryAdded(PathBuf, Result<helper::Scanned, Error>),
+    FileDropped(PathBuf),
+    WatchToggled(bool),
+    Rescan,
+    Rescanned(Result<helper::Scanned, Error>),
+    ImagesBatch(Result<Vec<ImageData>, Error>),
+    ImagesListed,
     ImagePoppedIn(Id),
-    ImageDownloaded(Result<Rgba, Error>),
-    ThumbnailDownloaded(Id, Result<Rgba, Error>),
+    ImageDownloaded(Id, Result<Rgba, Error>),
+    ImagePreloaded(Id, Result<Rgba, Error>),
+    MetadataLoaded(Id, Result<Metadata, Error>),
+    HistogramComputed(Id, Result<Histogram, Error>),
+    /// A thumbnail decoded for the generation of thumbnails it was
+    /// requested in.
+    ThumbnailDownloaded(Id, u32, Result<Rgba, Error>),
+    DetailsLoaded(Id, Result<Details, Error>),
     ThumbnailHovered(Id, bool),
+    ToggleSelect(Id),
+    ToggleFavorite(Id),
+    FavoritesLoaded(Result<Favorites, Error>),
+    FavoritesSaved(Result<(), Error>),
+    FavoritesOnlyToggled(bool),
+    /// Rates the image in the viewer, clearing its rating when 0.
+    RateCurrent(u8),
+    RatingsLoaded(Result<Ratings, Error>),
+    RatingsSaved(Result<(), Error>),
+    MinimumRatingChanged(MinimumRating),
+    SettingsSaved(Result<(), Error>),
+    ShowIncompleteToggled(bool),
+    CopySelected,
+    CopyDestinationChosen(Option<PathBuf>),
+    ImageCopied(String, Result<(), Error>),
+    RenameSelected,
+    RenameTemplateChanged(String),
+    ConfirmRename,
+    CancelRename,
+    Renamed(Result<Vec<(Id, PathBuf)>, Error>),
     Open(Id),
+    Compare,
+    /// Shows the difference between the two selected images.
+    DiffSelected,
+    DiffComputed(Id, Result<Diff, Error>),
+    DiffAmplificationChanged(u8),
+    AmplifyDiff,
+    DiffAmplified(Id, Result<Rgba, Error>),
     Close,
+    ContextMenu(Id),
+    ContextMenuSelected(Box<Message>),
+    CloseContextMenu,
+    Delete(Id),
+    DeleteCurrent,
+    CopyImage(Id),
+    CopyCurrent,
+    CopyPath(Id),
+    PathCopied(Result<(), Error>),
+    RevealInFiles(Id),
+    Revealed(Result<(), Error>),
+    OpenInMaps(Location),
+    MapsOpened(Result<(), Error>),
+    CopiedToClipboard(Result<(), Error>),
+    /// Saves a copy of the image in the viewer at its native resolution,
+    /// rotation included, in a file chosen by the user.
+    ExportCurrent(ExportFormat),
+    ExportDestinationChosen(Option<PathBuf>, ExportFormat, ImageData, u8),
+    Exported(PathBuf, Result<(), Error>),
+    /// Saves the image on the clipboard into the first folder and lists it.
+    PasteImage,
+    Pasted(Result<Option<PathBuf>, Error>),
+    Deleted(Id, Result<(), Error>),
+    RotateLeft,
+    RotateRight,
+    SaveRotation,
+    RotationSaved(Id, Result<(), Error>),
     Animate(Instant),
     ViewportChanged(Viewport),
     KeyPressed(Event),
+    Mouse(mouse::Event),
+    WindowResized(iced::Size),
+    /// Switches the window between fullscreen and windowed.
+    ToggleFullscreen,
+    DismissToast,
+    ThumbnailSizeChanged(ThumbnailSize),
+    ThumbnailFitChanged(ThumbnailFit),
+    ResizeFilterChanged(ResizeFilter),
+    ExportFormatChanged(ExportFormat),
+    ColumnCountChanged(Columns),
+    LayoutChanged(Layout),
+    SpacingChanged(Spacing),
+    SortOrderChanged(SortOrder),
+    PageStrideChanged(usize),
+    PageChanged(usize),
+    AnimationSpeedChanged(AnimationSpeed),
+    ReduceMotionToggled(bool),
+    MemoryBudgetChanged(MemoryBudget),
+    ThemeChanged(ThemeChoice),
+    FilterChanged(String),
+    FailuresToggled,
+    ScrollToTop,
+    RegenerateThumbnails,
+    HelpToggled,
+    #[cfg(feature = "metrics")]
+    MetricsToggled,
+}
+
+/// The state of copying the selected images into another folder.
+#[derive(Debug, Clone)]
+struct CopyProgress {
+    destination: PathBuf,
+    total: usize,
+    copied: usize,
+    /// The name of every image that could not be copied, with the reason.
+    failures: Vec<(String, Error)>,
+}
+
+impl CopyProgress {
+    /// How many failures are listed when the copy is done, so the toast
+    /// stays on screen.
+    const LISTED_FAILURES: usize = 5;
+
+    fn done(&self) -> usize {
+        self.copied + self.failures.len()
+    }
+
+    /// The outcome of the whole copy, listing the images that failed.
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "Copied {} of {} images to {}",
+            self.copied,
+            self.total,
+            self.destination.display()
+        );
+
+        let listed = self.failures.iter().take(Self::LISTED_FAILURES);
+
+        for (name, error) in listed {
+            summary.push_str(&format!("\n{name}: {error}"));
+        }
+
+        let unlisted =
+            self.failures.len().saturating_sub(Self::LISTED_FAILURES);
+
+        if unlisted > 0 {
+            summary.push_str(&format!("\nand {unlisted} more"));
+        }
+
+        summary
+    }
+}
+
+/// The state of the dialog renaming the selected images.
+#[derive(Debug, Clone)]
+struct Rename {
+    template: String,
+    is_renaming: bool,
+}
+
+/// Two selected images compared pixel by pixel in the viewer, which shows
+/// their difference.
+#[derive(Debug, Clone)]
+struct DiffMode {
+    before: Id,
+    after: Id,
+    /// The difference, once both images are decoded and compared.
+    diff: Option<Diff>,
+    amplification: u8,
+}
+
+/// A selection rectangle dragged across the grid, in the coordinates of
+/// its scrolled content.
+#[derive(Debug, Clone)]
+struct RubberBand {
+    origin: Point,
+    current: Point,
+    /// The selection before the drag started, which the cards within the
+    /// rectangle are added to. `None` until the cursor moves far enough
+    /// from the origin, so a click is not taken for a drag.
+    base: Option<HashSet<Id>>,
+}
+
+impl RubberBand {
+    fn bounds(&self) -> Rectangle {
+        let x = self.origin.x.min(self.current.x);
+        let y = self.origin.y.min(self.current.y);
+
+        Rectangle {
+            x,
+            y,
+            width: self.origin.x.max(self.current.x) - x,
+            height: self.origin.y.max(self.current.y) - y,
+        }
+    }
+}
+
+/// A child of the virtualized grid. The rows of cards are keyed by their
+/// first card, so their widget state stays with them as the rows before
+/// them are swapped in and out for the filler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GridRow {
+    Above,
+    Cards(Id),
+    Below,
+}
+
+/// Where a card is laid out, in the coordinates of the scrolled content.
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    id: Id,
+    /// The column holding the card.
+    lane: usize,
+    top: f32,
+    height: f32,
+}
+
+/// How many cards are laid out per row of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Columns {
+    /// As many as fit with the selected [`ThumbnailSize`].
+    #[default]
+    Auto,
+    Fixed(u8),
+}
+
+impl Columns {
+    pub const ALL: [Self; 8] = [
+        Self::Auto,
+        Self::Fixed(2),
+        Self::Fixed(3),
+        Self::Fixed(4),
+        Self::Fixed(5),
+        Self::Fixed(6),
+        Self::Fixed(7),
+        Self::Fixed(8),
+    ];
+}
+
+impl fmt::Display for Columns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => f.write_str("Auto"),
+            Self::Fixed(count) => write!(f, "{count} columns"),
+        }
+    }
+}
+
+/// How the cards are arranged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// Square cards in rows.
+    #[default]
+    Grid,
+    /// Cards keeping the aspect ratio of their image, packed into columns
+    /// of similar height.
+    Masonry,
+}
+
+impl Layout {
+    pub const ALL: [Self; 2] = [Self::Grid, Self::Masonry];
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Grid => "Grid",
+            Self::Masonry => "Masonry",
+        })
+    }
+}
+
+/// The order of the cards in the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    NameAsc,
+    NameDesc,
+}
+
+impl SortOrder {
+    pub const ALL: [Self; 2] = [Self::NameAsc, Self::NameDesc];
+
+    /// The name the order is saved under between sessions.
+    fn key(self) -> &'static str {
+        match self {
+            Self::NameAsc => "name_asc",
+            Self::NameDesc => "name_desc",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|order| order.key() == key)
+    }
+
+    fn compare(self, a: &ImageData, b: &ImageData) -> Ordering {
+        self.compare_names(&a.file_name(), &b.file_name())
+    }
+
+    fn compare_names(self, a: &str, b: &str) -> Ordering {
+        let ordering = helper::natural_cmp(a, b);
+
+        match self {
+            Self::NameAsc => ordering,
+            Self::NameDesc => ordering.reverse(),
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NameAsc => "Name (A–Z)",
+            Self::NameDesc => "Name (Z–A)",
+        })
+    }
+}
+
+/// How long fades and zooms take, if they animate at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationSpeed {
+    Off,
+    Fast,
+    #[default]
+    Normal,
+    Slow,
+}
+
+impl AnimationSpeed {
+    pub const ALL: [Self; 4] = [Self::Off, Self::Fast, Self::Normal, Self::Slow];
+
+    pub fn duration(self) -> Duration {
+        match self {
+            Self::Off => Duration::ZERO,
+            Self::Fast => Duration::from_millis(100),
+            Self::Normal => Duration::from_millis(200),
+            Self::Slow => Duration::from_millis(400),
+        }
+    }
+}
+
+impl fmt::Display for AnimationSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Fast => "Fast",
+            Self::Normal => "Normal",
+            Self::Slow => "Slow",
+        })
+    }
+}
+
+/// How much memory the decoded thumbnails may take up before the ones
+/// unseen for the longest are unloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryBudget {
+    Megabytes256,
+    Megabytes512,
+    #[default]
+    Gigabyte1,
+    Gigabytes2,
+    Unlimited,
+}
+
+impl MemoryBudget {
+    pub const ALL: [Self; 5] = [
+        Self::Megabytes256,
+        Self::Megabytes512,
+        Self::Gigabyte1,
+        Self::Gigabytes2,
+        Self::Unlimited,
+    ];
+
+    pub fn bytes(self) -> usize {
+        const MEGABYTE: usize = 1024 * 1024;
+
+        match self {
+            Self::Megabytes256 => 256 * MEGABYTE,
+            Self::Megabytes512 => 512 * MEGABYTE,
+            Self::Gigabyte1 => 1024 * MEGABYTE,
+            Self::Gigabytes2 => 2048 * MEGABYTE,
+            Self::Unlimited => usize::MAX,
+        }
+    }
+}
+
+impl fmt::Display for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Megabytes256 => "256 MB",
+            Self::Megabytes512 => "512 MB",
+            Self::Gigabyte1 => "1 GB",
+            Self::Gigabytes2 => "2 GB",
+            Self::Unlimited => "Unlimited",
+        })
+    }
+}
+
+/// The gap between the cards, and around the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spacing {
+    None,
+    Tight,
+    #[default]
+    Normal,
+    Relaxed,
+    Spacious,
+}
+
+impl Spacing {
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::Tight,
+        Self::Normal,
+        Self::Relaxed,
+        Self::Spacious,
+    ];
+
+    pub fn pixels(self) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::Tight => 2.0,
+            Self::Normal => 4.0,
+            Self::Relaxed => 8.0,
+            Self::Spacious => 16.0,
+        }
+    }
+}
+
+impl fmt::Display for Spacing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "None",
+            Self::Tight => "Tight",
+            Self::Normal => "Normal",
+            Self::Relaxed => "Relaxed",
+            Self::Spacious => "Spacious",
+        })
+    }
+}
+
+/// The fewest stars an image needs to be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MinimumRating {
+    #[default]
+    Any,
+    Stars(u8),
+}
+
+impl MinimumRating {
+    pub const ALL: [Self; 6] = [
+        Self::Any,
+        Self::Stars(1),
+        Self::Stars(2),
+        Self::Stars(3),
+        Self::Stars(4),
+        Self::Stars(5),
+    ];
+
+    fn allows(self, rating: u8) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Stars(stars) => rating >= stars,
+        }
+    }
+}
+
+impl fmt::Display for MinimumRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => f.write_str("Any rating"),
+            Self::Stars(stars) if *stars >= ratings::MAX => {
+                f.write_str(&ratings::stars(*stars))
+            }
+            Self::Stars(stars) => {
+                write!(f, "{} or more", ratings::stars(*stars))
+            }
+        }
+    }
+}
+
+/// A theme offered in the toolbar, or following the light or dark
+/// preference of the system.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThemeChoice {
+    System,
+    Theme(Theme),
+}
+
+impl ThemeChoice {
+    fn all() -> Vec<Self> {
+        std::iter::once(Self::System)
+            .chain(Theme::ALL.iter().cloned().map(Self::Theme))
+            .collect()
+    }
+}
+
+impl fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::System => f.write_str("System"),
+            Self::Theme(theme) => fmt::Display::fmt(theme, f),
+        }
+    }
 }
 
 impl Gallery {
-    pub fn new() -> Self {
-        Self {
+    /// The distance of the floating buttons from the edges of the grid.
+    const MARGIN: f32 = 4.0;
+    const SCROLLBAR_WIDTH: f32 = 10.0;
+    const FAILURES_HEIGHT: f32 = 150.0;
+
+    /// The amount of placeholder cards shown while the directories are
+    /// being listed.
+    const PLACEHOLDER_COUNT: usize = 12;
+
+    /// The amount of rows built beyond either edge of the viewport, so
+    /// cards are ready before they scroll in.
+    const OVERSCAN_ROWS: usize = 2;
+
+    /// The choices for how many images a page skips in the viewer.
+    const PAGE_STRIDES: [usize; 4] = [5, 10, 20, 50];
+
+    const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+    /// The most a difference of two images is multiplied by.
+    const MAX_AMPLIFICATION: u8 = 64;
+
+    /// How many images the filmstrip of the viewer shows on either side of
+    /// the current one.
+    const FILMSTRIP_REACH: usize = 15;
+
+    /// How far the cursor moves with the button held before a press over
+    /// the grid starts a selection rectangle.
+    const DRAG_THRESHOLD: f32 = 4.0;
+
+    /// Files dropped within this interval of each other belong to the same
+    /// drop.
+    const DROP_INTERVAL: Duration = Duration::from_millis(200);
+
+    /// How long the image at the scroll position stays next to the
+    /// scrollbar after scrolling stops.
+    const PEEK_DURATION: Duration = Duration::from_millis(600);
+    const PEEK_SIZE: u32 = 96;
+
+    /// How far the grid is scrolled before the button scrolling back to
+    /// the top appears.
+    const BACK_TO_TOP_THRESHOLD: f32 = 1000.0;
+
+    /// How often the directories are scanned for new files while watching.
+    const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+    /// Creates a gallery browsing the image files in `dirs` with the
+    /// `settings` of the last session, along with the task listing them. A
+    /// path to a file browses its folder and opens the file once it is
+    /// listed.
+    ///
+    /// Only files with one of the lowercase `extensions` are listed, like
+    /// the default [`helper::EXTENSIONS`], and only the first `limit` of
+    /// them in the sort order, if any.
+    pub fn new(
+        dirs: Vec<PathBuf>,
+        extensions: Vec<String>,
+        settings: Settings,
+        limit: Option<usize>,
+    ) -> (Self, Task<Message>) {
+        let mut gallery = Self {
             images: Vec::new(),
+            ids: Ids::default(),
             previews: HashMap::new(),
+            details: HashMap::new(),
+            selected_ids: HashSet::new(),
+            favorites: HashSet::new(),
+            favorite_paths: Favorites::new(),
+            favorites_only: false,
+            ratings: Ratings::new(),
+            minimum_rating: MinimumRating::default(),
+            show_incomplete: false,
+            selection_anchor: None,
+            focused_index: None,
+            last_click: None,
+            modifiers: keyboard::Modifiers::default(),
+            copy_progress: None,
+            rename: None,
+            is_help_visible: false,
+            #[cfg(feature = "metrics")]
+            is_metrics_visible: false,
+            thumbnails_requested: 0,
+            thumbnails_decoded: 0,
+            thumbnail_generation: 0,
             viewer: Viewer::new(),
+            context_menu: None,
+            cursor: Point::ORIGIN,
+            toast: None,
+            thumbnail_size: ThumbnailSize::default(),
+            thumbnail_fit: ThumbnailFit::default(),
+            resize_filter: ResizeFilter::default(),
+            export_format: ExportFormat::default(),
+            columns: Columns::default(),
+            layout: Layout::default(),
+            spacing: Spacing::default(),
+            page_stride: Self::PAGE_STRIDES[1],
+            page: 0,
+            sort_order: settings
+                .sort_order
+                .as_deref()
+                .and_then(SortOrder::from_key)
+                .unwrap_or_default(),
+            settings,
+            animation_speed: AnimationSpeed::default(),
+            reduce_motion: false,
+            back_to_top: Animation::new(false)
+                .duration(AnimationSpeed::default().duration())
+                .easing(animation::Easing::EaseInOut),
+            shimmer: Animation::new(false)
+                .duration(AnimationSpeed::default().duration() * 4)
+                .easing(animation::Easing::EaseInOut),
+            memory_budget: MemoryBudget::default(),
+            last_seen: HashMap::new(),
+            on_screen: HashSet::new(),
+            filter: String::new(),
+            scroll_offset: RelativeOffset::START,
+            grid_viewport: None,
+            grid_bounds: None,
+            rubber_band: None,
+            diff: None,
+            peek: None,
+            saved_offset: None,
+            window_size: iced::Size::ZERO,
+            theme: None,
             now: Instant::now(),
-            image_dir: None,
-        }
+            image_dirs: Vec::new(),
+            is_watching: false,
+            unsettled: HashSet::new(),
+            pending_open: None,
+            unreadable_dirs: Vec::new(),
+            extensions,
+            limit,
+            found: None,
+            failures: Vec::new(),
+            is_failures_expanded: false,
+            last_drop: None,
+            loaded: false,
+        };
+
+        let task = gallery.update(Message::OpenImageDirectories(dirs));
+
+        (gallery, task)
+    }
+
+    /// The theme picked in the toolbar, if any.
+    pub fn theme(&self) -> Option<Theme> {
+        self.theme.clone()
     }
 
-    pub fn theme(&self) -> Theme {
-        Theme::TokyoNight
+    /// The name of the current image while the viewer is open, or the
+    /// folders being browsed otherwise.
+    pub fn title(&self) -> String {
+        let subject = if let Some(id) = self.viewer.current_id() {
+            self.file_name(id)
+        } else {
+            self.image_dirs
+                .iter()
+                .map(|dir| {
+                    dir.file_name()
+                        .unwrap_or(dir.as_os_str())
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        format!("{subject} - Gallery")
     }
 
+    /// Listens to the keyboard, the mouse and the window, and drives the
+    /// animations while any is running.
     pub fn subscription(&self) -> Subscription<Message> {
         let is_animating = self
             .previews
             .values()
             .any(|preview| preview.is_animating(self.now))
-            || self.viewer.is_animating(self.now);
+            || self.viewer.is_animating(self.now)
+            || self.is_shimmering()
+            || self.is_peeking()
+            || self.back_to_top.is_animating(self.now);
 
-        let keyboard = event::listen().map(|event| {
-            if let IcedEvent::Keyboard(keyboard_event) = event {
-                Message::KeyPressed(keyboard_event)
-            } else {
-                Message::Animate(Instant::now())
+        // Mouse events are observed even when captured, since the viewer
+        // overlay captures the presses that start a pan.
+        let events = event::listen_with(|event, status, _window| match event {
+            IcedEvent::Keyboard(keyboard_event) => (status
+                == event::Status::Ignored)
+                .then_some(Message::KeyPressed(keyboard_event)),
+            IcedEvent::Mouse(mouse_event) => Some(Message::Mouse(mouse_event)),
+            IcedEvent::Window(
+                window::Event::Opened { size, .. } | window::Event::Resized(size),
+            ) => Some(Message::WindowResized(size)),
+            IcedEvent::Window(window::Event::FileDropped(path)) => {
+                Some(Message::FileDropped(path))
             }
+            _ => (status == event::Status::Ignored)
+                .then(|| Message::Animate(Instant::now())),
         });
 
+        let rescan = if self.is_watching {
+            time::every(Self::RESCAN_INTERVAL).map(|_| Message::Rescan)
+        } else {
+            Subscription::none()
+        };
+
         if is_animating {
             Subscription::batch([
                 window::frames().map(Message::Animate),
-                keyboard,
+                events,
+                rescan,
             ])
         } else {
-            keyboard
+            Subscription::batch([events, rescan])
         }
     }
 
+    /// Handles a [`Message`], returning the background work it starts.
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::OpenImageDirectory(path) => {
-                self.image_dir = Some(path.clone());
-                let paths = helper::list_image_files(path.to_str().unwrap_or_default());
-                Task::perform(
-                    async move { ImageData::list_from_paths(paths).await },
-                    Message::ImagesListed,
-                )
+            Message::OpenImageDirectories(paths) => {
+                // A single file opens its folder, showing the file in the
+                // viewer once it is listed. Archives and lists of URLs are
+                // browsed like folders
+                let mut dirs = Vec::new();
+
+                for path in paths {
+                    let dir = if path.is_file()
+                        && !is_archive(&path)
+                        && !is_url_list(&path)
+                    {
+                        let dir = path
+                            .parent()
+                            .filter(|dir| !dir.as_os_str().is_empty())
+                            .unwrap_or(Path::new("."))
+                            .to_path_buf();
+
+                        if self.pending_open.is_none() {
+                            self.pending_open = path
+                                .file_name()
+                                .map(|name| dir.join(name));
+                        }
+
+                        dir
+                    } else {
+                        path
+                    };
+
+                    if !dirs.contains(&dir) {
+                        dirs.push(dir);
+                    }
+                }
+
+                self.unreadable_dirs.clear();
+                self.found = None;
+                self.image_dirs = dirs.clone();
+                self.images.clear();
+                self.ids.clear();
+                self.failures.clear();
+                self.page = 0;
+                self.loaded = false;
+                self.discard_thumbnails();
+
+                // Reading large folders or archives takes a while, so it
+                // happens away from the window
+                Task::batch([
+                    Task::perform(
+                        helper::scan_image_files(
+                            dirs.clone(),
+                            self.extensions.clone(),
+                        ),
+                        move |result| Message::DirectoriesScanned(dirs, result),
+                    ),
+                    Task::perform(favorites::load(), Message::FavoritesLoaded),
+                    window::get_latest()
+                        .and_then(window::get_size)
+                        .map(Message::WindowResized),
+                ])
+            }
+            Message::DirectoriesScanned(dirs, Ok(scanned)) => {
+                // Another folder was opened in the meantime
+                if dirs != self.image_dirs {
+                    return Task::none();
+                }
+
+                let paths = self.take_scan(scanned);
+                let paths = self.cap(paths, 0);
+
+                // The first folder that could be read is offered again on
+                // the next launch
+                let last_folder = dirs
+                    .iter()
+                    .find(|dir| {
+                        dir.is_dir()
+                            && !self
+                                .unreadable_dirs
+                                .iter()
+                                .any(|(unreadable, _)| unreadable == *dir)
+                    })
+                    .map(|dir| {
+                        std::path::absolute(dir).unwrap_or_else(|_| dir.clone())
+                    });
+
+                let save = if last_folder.is_some()
+                    && last_folder != self.settings.last_folder
+                {
+                    self.settings.last_folder = last_folder;
+
+                    Task::perform(
+                        self.settings.clone().save(),
+                        Message::SettingsSaved,
+                    )
+                } else {
+                    Task::none()
+                };
+
+                Task::batch([list(paths, &self.ids), save])
+            }
+            Message::DirectoryAdded(dir, Ok(scanned)) => {
+                if !self.image_dirs.contains(&dir) {
+                    return Task::none();
+                }
+
+                let paths = self.take_scan(scanned);
+                let paths = self.cap(paths, self.images.len());
+
+                list(paths, &self.ids)
             }
-            Message::ImagesListed(Ok(images)) => {
-                self.images = images;
+            Message::DirectoriesScanned(_, Err(error))
+            | Message::DirectoryAdded(_, Err(error)) => {
+                self.loaded = true;
+                self.toast = Some(Toast::error(format!(
+                    "Failed to list the folders: {error}"
+                )));
                 Task::none()
             }
-            Message::ImagePoppedIn(id) => {
-                let Some(image) = self
-                    .images
+            Message::ImagesBatch(Ok(images)) => {
+                self.favorites.extend(
+                    images
+                        .iter()
+                        .filter(|image| self.favorite_paths.contains(image.path()))
+                        .map(|image| image.id),
+                );
+                // Overlapping directories list some files more than once
+                let mut listed: HashSet<Id> =
+                    self.images.iter().map(|image| image.id).collect();
+
+                let files: Vec<PathBuf> = images
                     .iter()
-                    .find(|candidate| candidate.id == id)
-                    .cloned()
-                else {
+                    .filter(|image| !listed.contains(&image.id))
+                    .filter_map(|image| image.file().map(Path::to_path_buf))
+                    .collect();
+
+                self.images.extend(
+                    images.into_iter().filter(|image| listed.insert(image.id)),
+                );
+                self.sort();
+
+                let ratings =
+                    Task::perform(ratings::load(files), Message::RatingsLoaded);
+
+                let pending = self.pending_open.as_ref().and_then(|path| {
+                    self.images.iter().find(|image| image.path() == path)
+                });
+
+                if let Some(image) = pending {
+                    let id = image.id;
+                    self.pending_open = None;
+
+                    let open = self.update(Message::Open(id));
+
+                    return Task::batch([ratings, open]);
+                }
+                ratings
+            }
+            Message::ImagesListed => {
+                self.loaded = true;
+                self.pending_open = None;
+                Task::none()
+            }
+            Message::AddDirectory(dir) => {
+                if self.image_dirs.contains(&dir) {
                     return Task::none();
-                };
+                }
+
+                self.image_dirs.push(dir.clone());
 
-                let id = id.clone();
                 Task::perform(
-                    image.download(Size::Thumbnail {
-                        width: Preview::WIDTH,
-                        height: Preview::HEIGHT,
-                    }),
-                    move |result| Message::ThumbnailDownloaded(id, result),
+                    helper::scan_image_files(
+                        vec![dir.clone()],
+                        self.extensions.clone(),
+                    ),
+                    move |result| Message::DirectoryAdded(dir, result),
                 )
             }
-            Message::ImageDownloaded(Ok(rgba)) => {
-                self.viewer.show(rgba);
-                Task::none()
-            }
-            Message::ThumbnailDownloaded(id, Ok(rgba)) => {
-                let thumbnail = if let Some(preview) = self.previews.remove(&id) {
-                    preview.load(rgba)
-                } else {
-                    Preview::ready(rgba)
-                };
+            Message::FileDropped(path) => {
+                let now = Instant::now();
+                let is_new_drop = self.last_drop.is_none_or(|dropped_at| {
+                    now.duration_since(dropped_at) > Self::DROP_INTERVAL
+                });
+
+                self.last_drop = Some(now);
+
+                // Holding shift replaces the gallery with the dropped files
+                if is_new_drop && self.modifiers.shift() {
+                    self.clear();
+                }
 
-                let _ = self.previews.insert(id, thumbnail);
+                if path.is_dir() || is_archive(&path) || is_url_list(&path) {
+                    return self.update(Message::AddDirectory(path));
+                }
+
+                let id = self.ids.of(&path);
+                let is_listed = self.images.iter().any(|image| image.id == id);
+
+                let is_supported =
+                    helper::is_supported_image(&path, &self.extensions);
+
+                if !is_supported || is_listed {
+                    return Task::none();
+                }
+
+                list(vec![path], &self.ids)
+            }
+            Message::WatchToggled(is_watching) => {
+                self.is_watching = is_watching;
+                self.unsettled.clear();
                 Task::none()
             }
-            Message::ThumbnailHovered(id, is_hovered) => {
-                if let Some(preview) = self.previews.get_mut(&id) {
-                    preview.toggle_zoom(is_hovered);
+            Message::Rescan => Task::perform(
+                helper::scan_image_files(
+                    self.image_dirs.clone(),
+                    self.extensions.clone(),
+                ),
+                Message::Rescanned,
+            ),
+            Message::Rescanned(Ok((paths, unreadable))) => {
+                // The scan repeats, so only the folders that just became
+                // unreadable are reported
+                let newly_unreadable = unreadable.iter().find(|(dir, _)| {
+                    !self.unreadable_dirs.iter().any(|(known, _)| known == dir)
+                });
+
+                if let Some((dir, error)) = newly_unreadable {
+                    self.toast = Some(Toast::error(format!(
+                        "Failed to rescan {}: {error}",
+                        dir.display()
+                    )));
+                }
+
+                self.unreadable_dirs = unreadable;
+
+                let listed: HashSet<Id> =
+                    self.images.iter().map(|image| image.id).collect();
+
+                let new: HashSet<PathBuf> = paths
+                    .into_iter()
+                    .filter(|path| {
+                        !self
+                            .ids
+                            .get(path)
+                            .is_some_and(|id| listed.contains(&id))
+                    })
+                    .collect();
+
+                // The files left out by the limit are found by every scan,
+                // so they are counted anew rather than added to the count
+                if let Some(limit) = self.limit {
+                    let found = self.images.len() + new.len();
+
+                    self.found = (found > limit).then_some(found);
+                }
+
+                let (mut settled, unsettled): (Vec<_>, Vec<_>) = new
+                    .into_iter()
+                    .partition(|path| self.unsettled.contains(path));
+
+                self.unsettled = unsettled.into_iter().collect();
+
+                if let Some(limit) = self.limit {
+                    let room = limit.saturating_sub(self.images.len());
+
+                    settled = self.first_in_order(settled, room);
+                }
+
+                if settled.is_empty() {
+                    Task::none()
+                } else {
+                    list(settled, &self.ids)
                 }
-                Task::none()
             }
-            Message::Open(id) => {
+            Message::ImagePoppedIn(id) => {
+                if let Some(
+                    Preview::Ready { .. }
+                    | Preview::Failed { .. }
+                    | Preview::Incomplete,
+                ) = self.previews.get(&id)
+                {
+                    return Task::none();
+                }
+
                 let Some(image) = self
                     .images
                     .iter()
@@ -144,92 +1187,3364 @@ impl Gallery {
                     return Task::none();
                 };
 
-                let current_index = self.images.iter().position(|img| img.id == id);
-                self.viewer.open();
-                self.viewer.set_current_id(Some(id));
-                self.viewer.set_current_index(current_index);
-                Task::perform(
-                    image.download(Size::Original),
-                    Message::ImageDownloaded,
-                )
+                // There is nothing to decode yet
+                if image.is_incomplete() {
+                    let _ = self.previews.insert(id, Preview::Incomplete);
+                    return Task::none();
+                }
+
+                self.thumbnails_requested += 1;
+                let _ = self.previews.insert(id, Preview::Loading);
+
+                let details = Task::perform(image.clone().details(), move |result| {
+                    Message::DetailsLoaded(id, result)
+                });
+
+                let (width, height) = self.card_dimensions(&image);
+                let generation = self.thumbnail_generation;
+
+                let thumbnail = Task::perform(
+                    image.download(Size::Thumbnail {
+                        width,
+                        height,
+                        filter: self.resize_filter,
+                    }),
+                    move |result| {
+                        Message::ThumbnailDownloaded(id, generation, result)
+                    },
+                );
+
+                Task::batch([thumbnail, details])
             }
-            Message::Close => {
-                self.viewer.close();
+            Message::ImageDownloaded(id, Ok(rgba)) => {
+                if self.viewer.compared_id() == Some(id) {
+                    self.viewer.show_compared(rgba);
+                } else if self.viewer.current_id() == Some(id)
+                    && self.diff.is_none()
+                {
+                    self.viewer.preload(id, rgba.clone());
+                    self.viewer.show(rgba.clone());
+
+                    return histogram(id, rgba);
+                } else if self.is_neighbor(id) {
+                    self.viewer.preload(id, rgba);
+                }
                 Task::none()
             }
-            Message::Animate(now) => {
-                self.now = now;
+            Message::HistogramComputed(id, result) => {
+                if self.viewer.current_id() == Some(id) {
+                    self.viewer.set_histogram(result);
+                }
                 Task::none()
             }
-            Message::ViewportChanged(viewport) => {
+            Message::ImagePreloaded(id, Ok(rgba)) => {
+                if self.is_neighbor(id) {
+                    self.viewer.preload(id, rgba);
+                }
                 Task::none()
             }
-            Message::KeyPressed(event) => {
-                if let Event::KeyPressed { key, .. } = event {
-                    if self.viewer.is_open(self.now) {
-                        match key {
-                            Key::Named(Named::ArrowLeft) => {
-                                let current_index = self.viewer.current_index().unwrap();
-                                if current_index > 0 {
-                                    let prev_image = &self.images[current_index - 1];
-                                    println!("Loading previous image: {:?}", prev_image);
-                                    self.viewer.set_current_index(Some(current_index - 1));
-                                    self.viewer.set_current_id(Some(prev_image.id));
-                                    return Task::perform(
-                                        prev_image.clone().download(Size::Original),
-                                        Message::ImageDownloaded,
-                                    );
-                                }
+            Message::ThumbnailDownloaded(_, generation, _)
+                if generation != self.thumbnail_generation =>
+            {
+                Task::none()
+            }
+            Message::ThumbnailDownloaded(id, _, Ok(rgba)) => {
+                let (speed, reduce_motion) =
+                    (self.animation_speed, self.reduce_motion);
+
+                let _ = self
+                    .previews
+                    .insert(id, Preview::ready(rgba, speed, reduce_motion));
+                let _ = self.last_seen.insert(id, Instant::now());
+                self.forget_failure(id);
+                self.count_decoded_thumbnail();
+                self.evict_thumbnails();
+                Task::none()
+            }
+            Message::DetailsLoaded(id, result) => {
+                let _ = self.details.insert(id, result);
+                Task::none()
+            }
+            Message::ThumbnailHovered(id, is_hovered) => {
+                let _ = self.last_seen.insert(id, Instant::now());
+
+                if let Some(preview) = self.previews.get_mut(&id) {
+                    preview.toggle_zoom(is_hovered, self.reduce_motion);
+                }
+                Task::none()
+            }
+            Message::MetadataLoaded(id, result) => {
+                if self.viewer.current_id() == Some(id) {
+                    self.viewer.set_metadata(result);
+                }
+                Task::none()
+            }
+            Message::ToggleSelect(id) => {
+                let now = Instant::now();
+                let is_double_click =
+                    self.last_click.is_some_and(|(last_id, clicked_at)| {
+                        last_id == id
+                            && now.duration_since(clicked_at)
+                                <= Self::DOUBLE_CLICK_INTERVAL
+                    });
+
+                if is_double_click {
+                    self.last_click = None;
+                    return self.update(Message::Open(id));
+                }
+
+                self.last_click = Some((id, now));
+                self.focused_index = self
+                    .page_images()
+                    .iter()
+                    .filter(|image| self.is_visible(image))
+                    .position(|image| image.id == id);
+
+                let anchor = self
+                    .selection_anchor
+                    .and_then(|anchor| self.position(anchor));
+
+                match (self.position(id), anchor) {
+                    (Some(index), Some(anchor)) if self.modifiers.shift() => {
+                        let range = index.min(anchor)..=index.max(anchor);
+
+                        if !self.modifiers.command() {
+                            self.selected_ids.clear();
+                        }
+
+                        let visible: Vec<Id> = self.images[range]
+                            .iter()
+                            .filter(|image| self.is_visible(image))
+                            .map(|image| image.id)
+                            .collect();
+
+                        self.selected_ids.extend(visible);
+                    }
+                    _ if self.modifiers.command() => {
+                        if !self.selected_ids.remove(&id) {
+                            let _ = self.selected_ids.insert(id);
+                        }
+                        self.selection_anchor = Some(id);
+                    }
+                    _ => {
+                        self.selected_ids.clear();
+                        let _ = self.selected_ids.insert(id);
+                        self.selection_anchor = Some(id);
+                    }
+                }
+                Task::none()
+            }
+            Message::ToggleFavorite(id) => {
+                let Some(image) = self.images.iter().find(|image| image.id == id)
+                else {
+                    return Task::none();
+                };
+
+                if self.favorites.remove(&id) {
+                    let _ = self.favorite_paths.remove(image.path());
+                } else {
+                    let _ = self.favorites.insert(id);
+                    let _ = self.favorite_paths.insert(image.path().to_path_buf());
+                }
+
+                Task::perform(
+                    favorites::save(self.favorite_paths.clone()),
+                    Message::FavoritesSaved,
+                )
+            }
+            Message::FavoritesLoaded(Ok(favorite_paths)) => {
+                // Keep anything starred while the favorites were loading
+                self.favorite_paths.extend(favorite_paths);
+                self.favorites = self
+                    .images
+                    .iter()
+                    .filter(|image| self.favorite_paths.contains(image.path()))
+                    .map(|image| image.id)
+                    .collect();
+                Task::none()
+            }
+            Message::FavoritesLoaded(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to load favorites: {error}"
+                )));
+                Task::none()
+            }
+            Message::FavoritesSaved(Ok(())) => Task::none(),
+            Message::FavoritesSaved(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to save favorites: {error}"
+                )));
+                Task::none()
+            }
+            Message::SettingsSaved(Ok(())) => Task::none(),
+            Message::SettingsSaved(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to save settings: {error}"
+                )));
+                Task::none()
+            }
+            Message::FavoritesOnlyToggled(favorites_only) => {
+                self.favorites_only = favorites_only;
+                Task::none()
+            }
+            Message::RateCurrent(rating) => {
+                let Some(image) = self
+                    .viewer
+                    .current_index()
+                    .and_then(|index| self.images.get(index))
+                else {
+                    return Task::none();
+                };
+
+                let Some(path) = image.file() else {
+                    self.toast = Some(Toast::info(
+                        "Only images in folders can be rated",
+                    ));
+                    return Task::none();
+                };
+
+                let path = path.to_path_buf();
+                let rating = rating.min(ratings::MAX);
+
+                if rating == 0 {
+                    let _ = self.ratings.remove(&path);
+                } else {
+                    let _ = self.ratings.insert(path.clone(), rating);
+                }
+
+                Task::perform(
+                    ratings::save(path, rating),
+                    Message::RatingsSaved,
+                )
+            }
+            Message::RatingsLoaded(Ok(ratings)) => {
+                // Keep anything rated while the ratings were loading
+                for (path, rating) in ratings {
+                    let _ = self.ratings.entry(path).or_insert(rating);
+                }
+                Task::none()
+            }
+            Message::RatingsLoaded(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to load ratings: {error}"
+                )));
+                Task::none()
+            }
+            Message::RatingsSaved(Ok(())) => Task::none(),
+            Message::RatingsSaved(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to save ratings: {error}"
+                )));
+                Task::none()
+            }
+            Message::MinimumRatingChanged(minimum_rating) => {
+                self.minimum_rating = minimum_rating;
+                Task::none()
+            }
+            Message::ShowIncompleteToggled(show_incomplete) => {
+                self.show_incomplete = show_incomplete;
+                Task::none()
+            }
+            Message::CopySelected => Task::perform(
+                async {
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Copy selected images to")
+                        .pick_folder()
+                        .await
+                        .map(|folder| folder.path().to_path_buf())
+                },
+                Message::CopyDestinationChosen,
+            ),
+            Message::CopyDestinationChosen(Some(destination)) => {
+                let selected: Vec<ImageData> = self
+                    .images
+                    .iter()
+                    .filter(|image| self.selected_ids.contains(&image.id))
+                    .cloned()
+                    .collect();
+
+                if selected.is_empty() {
+                    return Task::none();
+                }
+
+                self.copy_progress = Some(CopyProgress {
+                    destination: destination.clone(),
+                    total: selected.len(),
+                    copied: 0,
+                    failures: Vec::new(),
+                });
+
+                Task::batch(selected.into_iter().map(|image| {
+                    let name = image.file_name();
+
+                    Task::perform(
+                        image.copy_to(destination.clone()),
+                        move |result| Message::ImageCopied(name, result),
+                    )
+                }))
+            }
+            Message::CopyDestinationChosen(None) => Task::none(),
+            Message::ImageCopied(name, result) => {
+                let Some(progress) = &mut self.copy_progress else {
+                    return Task::none();
+                };
+
+                match result {
+                    Ok(()) => progress.copied += 1,
+                    Err(error) => progress.failures.push((name, error)),
+                }
+
+                if progress.done() == progress.total {
+                    let summary = progress.summary();
+
+                    self.toast = Some(if progress.failures.is_empty() {
+                        Toast::info(summary)
+                    } else {
+                        Toast::error(summary)
+                    });
+                    self.copy_progress = None;
+                }
+                Task::none()
+            }
+            Message::RenameSelected => {
+                if !self.selected_ids.is_empty() {
+                    self.rename = Some(Rename {
+                        template: String::from("{name}"),
+                        is_renaming: false,
+                    });
+                }
+                Task::none()
+            }
+            Message::RenameTemplateChanged(template) => {
+                if let Some(dialog) = &mut self.rename {
+                    dialog.template = template;
+                }
+                Task::none()
+            }
+            Message::ConfirmRename => {
+                let Some(dialog) = &self.rename else {
+                    return Task::none();
+                };
+
+                if dialog.is_renaming {
+                    return Task::none();
+                }
+
+                let Ok(renames) = self.rename_targets(&dialog.template) else {
+                    return Task::none();
+                };
+
+                if let Some(dialog) = &mut self.rename {
+                    dialog.is_renaming = true;
+                }
+
+                Task::perform(rename::rename(renames), Message::Renamed)
+            }
+            Message::CancelRename => {
+                self.rename = None;
+                Task::none()
+            }
+            Message::Renamed(Ok(renamed)) => {
+                self.rename = None;
+
+                let mut are_favorites_renamed = false;
+
+                for (id, path) in &renamed {
+                    let Some(image) =
+                        self.images.iter_mut().find(|image| image.id == *id)
+                    else {
+                        continue;
+                    };
+
+                    if self.favorite_paths.remove(image.path()) {
+                        let _ = self.favorite_paths.insert(path.clone());
+                        are_favorites_renamed = true;
+                    }
+
+                    // Their sidecars were moved along with them
+                    if let Some(rating) = self.ratings.remove(image.path()) {
+                        let _ = self.ratings.insert(path.clone(), rating);
+                    }
+
+                    if let Some(name) = path.file_name() {
+                        self.ids.rename(*id, name);
+                    }
+
+                    image.set_path(path.clone());
+                }
+
+                self.sort();
+
+                self.toast = Some(Toast::info(format!(
+                    "Renamed {} images",
+                    renamed.len()
+                )));
+
+                if are_favorites_renamed {
+                    Task::perform(
+                        favorites::save(self.favorite_paths.clone()),
+                        Message::FavoritesSaved,
+                    )
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Renamed(Err(error)) => {
+                // Keep the dialog open, so the pattern can be fixed
+                if let Some(dialog) = &mut self.rename {
+                    dialog.is_renaming = false;
+                }
+
+                self.toast = Some(Toast::error(format!(
+                    "Failed to rename images: {error}"
+                )));
+                Task::none()
+            }
+            Message::Open(id) => {
+                let Some(index) =
+                    self.images.iter().position(|image| image.id == id)
+                else {
+                    return Task::none();
+                };
+
+                // Images opened from elsewhere may be on another page
+                let page = index / ImageData::LIMIT;
+
+                if page != self.page_range().start / ImageData::LIMIT {
+                    self.show_page(page);
+                    self.scroll_offset = RelativeOffset::START;
+                }
+
+                // The filmstrip moves between images like the arrow keys
+                if self.viewer.is_open(self.now) && !self.viewer.is_comparing()
+                {
+                    return self.go_to(index);
+                }
+
+                self.saved_offset = Some(self.scroll_offset);
+                self.viewer.open();
+                self.go_to(index)
+            }
+            Message::Compare => {
+                let selected: Vec<ImageData> = self
+                    .images
+                    .iter()
+                    .filter(|image| self.selected_ids.contains(&image.id))
+                    .cloned()
+                    .collect();
+
+                let [left, right] = &selected[..] else {
+                    return Task::none();
+                };
+
+                let Some(index) = self.position(left.id) else {
+                    return Task::none();
+                };
+
+                self.saved_offset = Some(self.scroll_offset);
+                self.viewer.open();
+                self.viewer.compare(index, left.id, right.id);
+
+                Task::batch([left, right].map(|image| {
+                    let id = image.id;
+
+                    Task::perform(
+                        image.clone().download(Size::Original),
+                        move |result| Message::ImageDownloaded(id, result),
+                    )
+                }))
+            }
+            Message::DiffSelected => {
+                let selected: Vec<ImageData> = self
+                    .images
+                    .iter()
+                    .filter(|image| self.selected_ids.contains(&image.id))
+                    .cloned()
+                    .collect();
+
+                let [before, after] = &selected[..] else {
+                    return Task::none();
+                };
+
+                let Some(index) = self.position(before.id) else {
+                    return Task::none();
+                };
+
+                self.saved_offset = Some(self.scroll_offset);
+                self.viewer.open();
+                self.viewer.go_to(index, before.id);
+                self.diff = Some(DiffMode {
+                    before: before.id,
+                    after: after.id,
+                    diff: None,
+                    amplification: 1,
+                });
+
+                let id = before.id;
+                let originals = future::try_join(
+                    before.clone().download(Size::Original),
+                    after.clone().download(Size::Original),
+                );
+
+                Task::perform(
+                    async move {
+                        let (before, after) = originals.await?;
+
+                        Diff::compute(before, after).await
+                    },
+                    move |result| Message::DiffComputed(id, result),
+                )
+            }
+            Message::DiffComputed(id, Ok(diff)) => {
+                let Some(mode) = &mut self.diff else {
+                    return Task::none();
+                };
+
+                if mode.before != id {
+                    return Task::none();
+                }
+
+                mode.diff = Some(diff);
+                self.update(Message::AmplifyDiff)
+            }
+            Message::DiffAmplificationChanged(amplification) => {
+                if let Some(mode) = &mut self.diff {
+                    mode.amplification = amplification;
+                }
+                Task::none()
+            }
+            Message::AmplifyDiff => {
+                let Some(DiffMode {
+                    before,
+                    diff: Some(diff),
+                    amplification,
+                    ..
+                }) = &self.diff
+                else {
+                    return Task::none();
+                };
+
+                let id = *before;
+
+                Task::perform(
+                    diff.clone().amplify(*amplification),
+                    move |result| Message::DiffAmplified(id, result),
+                )
+            }
+            Message::DiffAmplified(id, Ok(rgba)) => {
+                let is_current = self.diff.as_ref().is_some_and(|mode| {
+                    mode.before == id && self.viewer.current_id() == Some(id)
+                });
+
+                if !is_current {
+                    return Task::none();
+                }
+
+                self.viewer.show(rgba.clone());
+                histogram(id, rgba)
+            }
+            Message::DiffComputed(id, Err(error))
+            | Message::DiffAmplified(id, Err(error)) => {
+                if self.diff.as_ref().is_some_and(|mode| mode.before == id) {
+                    self.viewer.fail();
+                    self.toast = Some(Toast::error(format!(
+                        "Failed to compare the images: {error}"
+                    )));
+                }
+                Task::none()
+            }
+            Message::Close => {
+                self.viewer.close();
+                self.diff = None;
+
+                match self.saved_offset.take() {
+                    Some(offset) => scrollable::snap_to(GRID.clone(), offset),
+                    None => Task::none(),
+                }
+            }
+            Message::ContextMenu(id) => {
+                self.context_menu = Some((id, self.cursor));
+                Task::none()
+            }
+            Message::ContextMenuSelected(message) => {
+                self.context_menu = None;
+                self.update(*message)
+            }
+            Message::CloseContextMenu => {
+                self.context_menu = None;
+                Task::none()
+            }
+            Message::Delete(id) => {
+                let Some(image) =
+                    self.images.iter().find(|image| image.id == id).cloned()
+                else {
+                    return Task::none();
+                };
+
+                Task::perform(image.trash(), move |result| {
+                    Message::Deleted(id, result)
+                })
+            }
+            Message::DeleteCurrent => match self.viewer.current_id() {
+                Some(id) => self.update(Message::Delete(id)),
+                None => Task::none(),
+            },
+            Message::CopyImage(id) => {
+                let Some(image) =
+                    self.images.iter().find(|image| image.id == id).cloned()
+                else {
+                    return Task::none();
+                };
+
+                Task::perform(
+                    async move {
+                        image
+                            .download(Size::Original)
+                            .await?
+                            .copy_to_clipboard()
+                            .await
+                    },
+                    Message::CopiedToClipboard,
+                )
+            }
+            Message::CopyCurrent => {
+                let Some(rgba) = self.viewer.current_image() else {
+                    return Task::none();
+                };
+
+                Task::perform(
+                    rgba.copy_to_clipboard(),
+                    Message::CopiedToClipboard,
+                )
+            }
+            Message::CopiedToClipboard(Ok(())) => {
+                self.toast = Some(Toast::info("Copied image to the clipboard"));
+                Task::none()
+            }
+            Message::CopiedToClipboard(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to copy image to the clipboard: {error}"
+                )));
+                Task::none()
+            }
+            Message::ExportCurrent(format) => {
+                let Some(image) = self
+                    .viewer
+                    .current_index()
+                    .and_then(|index| self.images.get(index))
+                    .cloned()
+                else {
+                    return Task::none();
+                };
+
+                let quarter_turns = self.viewer.pending_rotation().unwrap_or(0);
+
+                let name = format!(
+                    "{}.{}",
+                    image
+                        .path()
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy())
+                        .unwrap_or("image".into()),
+                    format.extension()
+                );
+
+                let dir = image
+                    .path()
+                    .parent()
+                    .filter(|dir| dir.is_dir())
+                    .map(Path::to_path_buf);
+
+                Task::perform(
+                    async move {
+                        let mut dialog = rfd::AsyncFileDialog::new()
+                            .set_title("Save image as")
+                            .set_file_name(name)
+                            .add_filter(
+                                format.to_string(),
+                                &[format.extension()],
+                            );
+
+                        if let Some(dir) = dir {
+                            dialog = dialog.set_directory(dir);
+                        }
+
+                        dialog
+                            .save_file()
+                            .await
+                            .map(|file| file.path().to_path_buf())
+                    },
+                    move |path| {
+                        Message::ExportDestinationChosen(
+                            path,
+                            format,
+                            image,
+                            quarter_turns,
+                        )
+                    },
+                )
+            }
+            Message::ExportDestinationChosen(
+                Some(path),
+                format,
+                image,
+                quarter_turns,
+            ) => Task::perform(
+                image.export(path.clone(), format, quarter_turns),
+                move |result| Message::Exported(path, result),
+            ),
+            Message::ExportDestinationChosen(None, ..) => Task::none(),
+            Message::Exported(path, Ok(())) => {
+                self.toast = Some(Toast::info(format!(
+                    "Saved {}",
+                    file_name(&path)
+                )));
+                Task::none()
+            }
+            Message::Exported(path, Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to save {}: {error}",
+                    file_name(&path)
+                )));
+                Task::none()
+            }
+            Message::PasteImage => {
+                let Some(dir) = self.image_dirs.first().cloned() else {
+                    self.toast = Some(Toast::error(
+                        "Open a folder to paste images into",
+                    ));
+                    return Task::none();
+                };
+
+                Task::perform(Rgba::paste_from_clipboard(dir), Message::Pasted)
+            }
+            Message::Pasted(Ok(Some(path))) => {
+                let name = path.file_name().map_or_else(
+                    || path.to_string_lossy(),
+                    |name| name.to_string_lossy(),
+                );
+
+                self.toast = Some(Toast::info(format!("Pasted as {name}")));
+                list(vec![path], &self.ids)
+            }
+            Message::Pasted(Ok(None)) => {
+                self.toast = Some(Toast::info("There is no image to paste"));
+                Task::none()
+            }
+            Message::Pasted(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to paste the image: {error}"
+                )));
+                Task::none()
+            }
+            Message::CopyPath(id) => {
+                let Some(image) =
+                    self.images.iter().find(|image| image.id == id).cloned()
+                else {
+                    return Task::none();
+                };
+
+                Task::perform(
+                    image.copy_path_to_clipboard(),
+                    Message::PathCopied,
+                )
+            }
+            Message::PathCopied(Ok(())) => {
+                self.toast = Some(Toast::info("Path copied"));
+                Task::none()
+            }
+            Message::PathCopied(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to copy the path: {error}"
+                )));
+                Task::none()
+            }
+            Message::RevealInFiles(id) => {
+                let Some(image) =
+                    self.images.iter().find(|image| image.id == id).cloned()
+                else {
+                    return Task::none();
+                };
+
+                Task::perform(image.reveal(), Message::Revealed)
+            }
+            Message::OpenInMaps(location) => Task::perform(
+                helper::open_url(location.url()),
+                Message::MapsOpened,
+            ),
+            Message::MapsOpened(Ok(())) => Task::none(),
+            Message::MapsOpened(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to open the browser: {error}"
+                )));
+                Task::none()
+            }
+            Message::Revealed(Ok(())) => Task::none(),
+            Message::Revealed(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to open the file manager: {error}"
+                )));
+                Task::none()
+            }
+            Message::RotateLeft => self.rotate_current(false),
+            Message::RotateRight => self.rotate_current(true),
+            Message::SaveRotation => {
+                let (Some(id), Some(quarter_turns)) =
+                    (self.viewer.current_id(), self.viewer.pending_rotation())
+                else {
+                    return Task::none();
+                };
+
+                let Some(image) =
+                    self.images.iter().find(|image| image.id == id).cloned()
+                else {
+                    return Task::none();
+                };
+
+                if let Some(rotated) = self.viewer.confirm_rotation() {
+                    self.viewer.preload(id, rotated);
+                }
+
+                Task::perform(image.rotate(quarter_turns), move |result| {
+                    Message::RotationSaved(id, result)
+                })
+            }
+            Message::RotationSaved(id, Ok(())) => {
+                // The card pops in again and decodes a fresh thumbnail
+                let _ = self.previews.remove(&id);
+                let _ = self.details.remove(&id);
+                Task::none()
+            }
+            Message::Deleted(id, Ok(())) => self.remove(id),
+            Message::Animate(now) => {
+                self.now = now;
+                self.viewer.tick(now);
+
+                if self.is_shimmering() && !self.shimmer.is_animating(now) {
+                    let is_bright =
+                        self.shimmer.interpolate(0.0, 1.0, now) >= 1.0;
+
+                    self.shimmer.go_mut(!is_bright);
+                }
+
+                Task::none()
+            }
+            Message::ViewportChanged(viewport) => {
+                self.scroll_offset = viewport.relative_offset();
+                self.grid_viewport = Some((
+                    viewport.absolute_offset().y,
+                    viewport.bounds().height,
+                ));
+                self.grid_bounds = Some(viewport.bounds());
+                self.touch_on_screen(&viewport);
+                self.peek = self
+                    .topmost(&viewport)
+                    .map(|id| (id, Instant::now()));
+
+                let is_deep =
+                    viewport.absolute_offset().y > Self::BACK_TO_TOP_THRESHOLD;
+
+                if self.back_to_top.value() != is_deep {
+                    self.back_to_top.go_mut(is_deep);
+                }
+                Task::none()
+            }
+            Message::RegenerateThumbnails => {
+                self.discard_thumbnails();
+                self.details.clear();
+                self.failures.clear();
+                self.last_seen.clear();
+
+                // The decoders limit how many run at once, and the progress
+                // bar counts them down
+                let ids: Vec<Id> = self
+                    .page_images()
+                    .iter()
+                    .filter(|image| self.is_visible(image))
+                    .map(|image| image.id)
+                    .collect();
+
+                Task::batch(
+                    ids.into_iter()
+                        .map(|id| self.update(Message::ImagePoppedIn(id))),
+                )
+            }
+            Message::HelpToggled => {
+                self.is_help_visible = !self.is_help_visible;
+                Task::none()
+            }
+            #[cfg(feature = "metrics")]
+            Message::MetricsToggled => {
+                self.is_metrics_visible = !self.is_metrics_visible;
+                Task::none()
+            }
+            Message::ScrollToTop => {
+                scrollable::snap_to(GRID.clone(), RelativeOffset::START)
+            }
+            Message::KeyPressed(Event::ModifiersChanged(modifiers)) => {
+                self.modifiers = modifiers;
+                Task::none()
+            }
+            Message::KeyPressed(event) => {
+                if let Event::KeyPressed { key, .. } = event {
+                    let is_help_key = key.as_ref() == Key::Character("?");
+
+                    if self.is_help_visible {
+                        if is_help_key || key == Key::Named(Named::Escape) {
+                            self.is_help_visible = false;
+                        }
+                    } else if is_help_key {
+                        self.is_help_visible = true;
+                    } else if key == Key::Named(Named::F11) {
+                        return self.update(Message::ToggleFullscreen);
+                    } else if key == Key::Named(Named::F12) {
+                        #[cfg(feature = "metrics")]
+                        return self.update(Message::MetricsToggled);
+                    } else if self.context_menu.is_some()
+                        && key == Key::Named(Named::Escape)
+                    {
+                        self.context_menu = None;
+                    } else if self.rename.is_some()
+                        && key == Key::Named(Named::Escape)
+                    {
+                        return self.update(Message::CancelRename);
+                    } else if self.viewer.is_comparing() {
+                        if key == Key::Named(Named::Escape) {
+                            return self.update(Message::Close);
+                        }
+                    } else if self.viewer.is_open(self.now) {
+                        let current_index =
+                            self.viewer.current().map(|(index, _)| index);
+
+                        match key.as_ref() {
+                            Key::Named(Named::ArrowLeft) => {
+                                let previous = current_index
+                                    .and_then(|index| self.previous_visible(index));
+
+                                if let Some(previous) = previous {
+                                    return self.go_to(previous);
+                                }
                             }
                             Key::Named(Named::ArrowRight) => {
-                                let current_index = self.viewer.current_index().unwrap();
-                                if current_index < self.images.len() - 1 {
-                                    let next_image = &self.images[current_index + 1];
-                                    println!("Loading next image: {:?}", next_image);
-                                    self.viewer.set_current_index(Some(current_index + 1));
-                                    self.viewer.set_current_id(Some(next_image.id));
-                                    return Task::perform(
-                                        next_image.clone().download(Size::Original),
-                                        Message::ImageDownloaded,
-                                    );
+                                let next = current_index
+                                    .and_then(|index| self.next_visible(index));
+
+                                if let Some(next) = next {
+                                    return self.go_to(next);
+                                }
+                            }
+                            Key::Named(Named::PageUp) => {
+                                let previous = current_index
+                                    .and_then(|index| self.page(index, false));
+
+                                if let Some(previous) = previous {
+                                    return self.go_to(previous);
+                                }
+                            }
+                            Key::Named(Named::PageDown) => {
+                                let next = current_index
+                                    .and_then(|index| self.page(index, true));
+
+                                if let Some(next) = next {
+                                    return self.go_to(next);
+                                }
+                            }
+                            Key::Named(Named::Home) => {
+                                let start = self.page_range().start;
+
+                                if let Some(first) = self
+                                    .page_images()
+                                    .iter()
+                                    .position(|image| self.is_visible(image))
+                                {
+                                    return self.go_to(start + first);
+                                }
+                            }
+                            Key::Named(Named::End) => {
+                                let start = self.page_range().start;
+
+                                if let Some(last) = self
+                                    .page_images()
+                                    .iter()
+                                    .rposition(|image| self.is_visible(image))
+                                {
+                                    return self.go_to(start + last);
                                 }
                             }
                             Key::Named(Named::Escape) => {
-                                self.viewer.close();
+                                return self.update(Message::Close);
+                            }
+                            Key::Named(Named::Delete) => {
+                                return self.update(Message::DeleteCurrent);
+                            }
+                            Key::Character("[") => {
+                                return self.update(Message::RotateLeft);
+                            }
+                            Key::Character("]") => {
+                                return self.update(Message::RotateRight);
+                            }
+                            Key::Named(Named::Enter) => {
+                                return self.update(Message::SaveRotation);
+                            }
+                            Key::Character("i") => {
+                                self.viewer.toggle_info();
+                            }
+                            Key::Character("n") => {
+                                self.viewer.toggle_caption();
+                            }
+                            Key::Character("t") => {
+                                self.viewer.toggle_filmstrip();
+                            }
+                            Key::Character(
+                                digit @ ("0" | "1" | "2" | "3" | "4" | "5"),
+                            ) if !self.modifiers.command() => {
+                                let rating = digit.parse().unwrap_or_default();
+
+                                return self
+                                    .update(Message::RateCurrent(rating));
+                            }
+                            Key::Character("c") if self.modifiers.command() => {
+                                return self.update(Message::CopyCurrent);
+                            }
+                            Key::Character("s") if self.modifiers.command() => {
+                                return self.update(Message::ExportCurrent(
+                                    self.export_format,
+                                ));
+                            }
+                            Key::Character("p") => {
+                                if let Some(id) = self.viewer.current_id() {
+                                    return self.update(Message::CopyPath(id));
+                                }
+                            }
+                            Key::Character("r") => {
+                                if let Some(id) = self.viewer.current_id() {
+                                    return self.update(Message::RevealInFiles(id));
+                                }
+                            }
+                            Key::Character("h") => {
+                                self.viewer.toggle_histogram();
+                            }
+                            Key::Character("b") => {
+                                self.viewer.cycle_background();
+                            }
+                            Key::Character("f") => {
+                                self.viewer.toggle_mode();
+
+                                return self.load_actual_size();
                             }
                             _ => {}
                         }
+                    } else {
+                        match key.as_ref() {
+                            Key::Character("c") if !self.modifiers.command() => {
+                                return self.update(Message::Compare);
+                            }
+                            Key::Character("d") if !self.modifiers.command() => {
+                                return self.update(Message::DiffSelected);
+                            }
+                            Key::Character("v") if self.modifiers.command() => {
+                                return self.update(Message::PasteImage);
+                            }
+                            Key::Named(Named::ArrowLeft) => {
+                                return self.move_focus(-1);
+                            }
+                            Key::Named(Named::ArrowRight) => {
+                                return self.move_focus(1);
+                            }
+                            Key::Named(Named::ArrowUp) => {
+                                let offset = self.vertical_offset(false);
+
+                                return self.move_focus(offset);
+                            }
+                            Key::Named(Named::ArrowDown) => {
+                                let offset = self.vertical_offset(true);
+
+                                return self.move_focus(offset);
+                            }
+                            Key::Named(Named::Enter) => {
+                                if let Some(id) = self.focused_id() {
+                                    return self.update(Message::Open(id));
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Task::none()
+            }
+            Message::ThumbnailSizeChanged(size) => {
+                self.thumbnail_size = size;
+
+                // Cards without a preview pop in again and request a
+                // thumbnail at the new size.
+                self.discard_thumbnails();
+                Task::none()
+            }
+            Message::Mouse(event) => {
+                if let mouse::Event::CursorMoved { position } = event {
+                    self.cursor = position;
+                }
+
+                if self.viewer.is_open(self.now) {
+                    match event {
+                        mouse::Event::WheelScrolled { delta } => {
+                            self.viewer.zoom(delta);
+                        }
+                        mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                            self.viewer.start_drag();
+                        }
+                        mouse::Event::ButtonReleased(mouse::Button::Left) => {
+                            self.viewer.end_drag();
+                        }
+                        mouse::Event::CursorMoved { position } => {
+                            self.viewer.move_cursor(position);
+                        }
+                        _ => {}
+                    }
+                } else {
+                    self.drag_rubber_band(event);
+                }
+                Task::none()
+            }
+            Message::ToggleFullscreen => {
+                window::get_latest().and_then(|id| {
+                    window::get_mode(id).then(move |mode| {
+                        let mode = if mode == window::Mode::Fullscreen {
+                            window::Mode::Windowed
+                        } else {
+                            window::Mode::Fullscreen
+                        };
+
+                        window::change_mode(id, mode)
+                    })
+                })
+            }
+            Message::WindowResized(size) => {
+                self.window_size = size;
+                self.viewer.resize(size);
+                Task::none()
+            }
+            Message::ThumbnailFitChanged(fit) => {
+                self.thumbnail_fit = fit;
+                Task::none()
+            }
+            Message::ExportFormatChanged(format) => {
+                self.export_format = format;
+                Task::none()
+            }
+            Message::ResizeFilterChanged(filter) => {
+                self.resize_filter = filter;
+
+                // Like a new size, thumbnails are scaled down again once
+                // their card pops in
+                self.discard_thumbnails();
+                Task::none()
+            }
+            Message::AnimationSpeedChanged(speed) => {
+                self.animation_speed = speed;
+                self.shimmer =
+                    self.shimmer.clone().duration(speed.duration() * 4);
+                self.back_to_top =
+                    self.back_to_top.clone().duration(speed.duration());
+                self.viewer.set_animation_speed(speed);
+                Task::none()
+            }
+            Message::ReduceMotionToggled(reduce_motion) => {
+                self.reduce_motion = reduce_motion;
+                self.viewer.set_reduce_motion(reduce_motion);
+
+                for preview in self.previews.values_mut() {
+                    preview.toggle_zoom(false, reduce_motion);
+                }
+                Task::none()
+            }
+            Message::MemoryBudgetChanged(budget) => {
+                self.memory_budget = budget;
+                self.evict_thumbnails();
+                Task::none()
+            }
+            Message::PageStrideChanged(stride) => {
+                self.page_stride = stride;
+                Task::none()
+            }
+            Message::PageChanged(page) => {
+                self.show_page(page);
+                scrollable::snap_to(GRID.clone(), RelativeOffset::START)
+            }
+            Message::SortOrderChanged(order) => {
+                self.sort_order = order;
+                self.sort();
+
+                self.settings.sort_order = Some(order.key().to_owned());
+                Task::perform(
+                    self.settings.clone().save(),
+                    Message::SettingsSaved,
+                )
+            }
+            Message::LayoutChanged(layout) => {
+                self.layout = layout;
+
+                // Cards change their shape, so thumbnails are decoded again
+                // at the new size
+                self.discard_thumbnails();
+                Task::none()
+            }
+            Message::SpacingChanged(spacing) => {
+                self.spacing = spacing;
+                Task::none()
+            }
+            Message::ColumnCountChanged(columns) => {
+                self.columns = columns;
+                Task::none()
+            }
+            Message::FilterChanged(filter) => {
+                self.filter = filter;
+                Task::none()
+            }
+            Message::ThemeChanged(choice) => {
+                self.theme = match choice {
+                    ThemeChoice::System => None,
+                    ThemeChoice::Theme(theme) => Some(theme),
+                };
+                Task::none()
+            }
+            Message::DismissToast => {
+                self.toast = None;
+                Task::none()
+            }
+            Message::ImagesBatch(Err(error)) => {
+                self.toast =
+                    Some(Toast::error(format!("Failed to list images: {error}")));
+                Task::none()
+            }
+            Message::ImageDownloaded(id, Err(error))
+                if error.is_not_found() =>
+            {
+                self.toast = Some(Toast::error("File no longer exists"));
+                self.remove(id)
+            }
+            Message::ImagePreloaded(id, Err(error)) if error.is_not_found() => {
+                self.remove(id)
+            }
+            Message::ImageDownloaded(id, Err(error)) => {
+                // The file may have become unreadable since its thumbnail
+                // was decoded
+                if let Error::IOFailed(_) = error {
+                    let _ = self.previews.insert(id, Preview::failed(error.clone()));
+                }
+
+                if self.viewer.current_id() == Some(id)
+                    || self.viewer.compared_id() == Some(id)
+                {
+                    let name = self.file_name(id);
+
+                    self.viewer.fail();
+                    self.toast = Some(Toast::error(format!(
+                        "Failed to open {name}: {error}"
+                    )));
+                }
+                Task::none()
+            }
+            Message::ThumbnailDownloaded(id, _, Err(error)) => {
+                self.forget_failure(id);
+
+                if let Some(position) = self.position(id) {
+                    let path = self.images[position].path().to_path_buf();
+
+                    self.failures.push((path, error.clone()));
+                }
+
+                let _ = self.previews.insert(id, Preview::failed(error));
+                self.count_decoded_thumbnail();
+                Task::none()
+            }
+            Message::FailuresToggled => {
+                self.is_failures_expanded = !self.is_failures_expanded;
+                Task::none()
+            }
+            Message::RotationSaved(id, Err(error)) => {
+                let name = self.file_name(id);
+
+                self.toast = Some(Toast::error(format!(
+                    "Failed to save rotation of {name}: {error}"
+                )));
+                Task::none()
+            }
+            Message::Deleted(id, Err(error)) => {
+                let name = self.file_name(id);
+
+                self.toast = Some(Toast::error(format!(
+                    "Failed to delete {name}: {error}"
+                )));
+                Task::none()
+            }
+            Message::Rescanned(Err(error)) => {
+                self.toast = Some(Toast::error(format!(
+                    "Failed to rescan the folders: {error}"
+                )));
+                Task::none()
+            }
+            // A neighbor failing to preload is reported once it is opened,
+            // since opening it decodes it again
+            Message::ImagePreloaded(_, Err(_)) => Task::none(),
+        }
+    }
+
+    /// Turns the image in the viewer by a quarter turn, refusing before
+    /// anything is confirmed if its file cannot be rotated without loss.
+    fn rotate_current(&mut self, clockwise: bool) -> Task<Message> {
+        // The difference of two images is not theirs to rotate
+        if self.diff.is_some() {
+            return Task::none();
+        }
+
+        let Some(image) = self
+            .viewer
+            .current_index()
+            .and_then(|index| self.images.get(index))
+        else {
+            return Task::none();
+        };
+
+        if !image.can_rotate() {
+            self.toast = Some(Toast::error(format!(
+                "Cannot rotate {}: only JPEG and PNG files can be rotated",
+                image.file_name()
+            )));
+            return Task::none();
+        }
+
+        self.viewer.rotate(clockwise);
+        Task::none()
+    }
+
+    /// Shows the image at `index` in the viewer, using a preloaded original
+    /// if available, and starts preloading its neighbors.
+    fn go_to(&mut self, index: usize) -> Task<Message> {
+        let Some(image) = self.images.get(index).cloned() else {
+            return Task::none();
+        };
+
+        self.viewer.go_to(index, image.id);
+        self.diff = None;
+
+        let neighbors: Vec<ImageData> =
+            [self.previous_visible(index), self.next_visible(index)]
+            .into_iter()
+            .flatten()
+            .filter_map(|neighbor| self.images.get(neighbor))
+            .cloned()
+            .collect();
+
+        self.viewer.retain_preloaded(|id| {
+            id == image.id || neighbors.iter().any(|neighbor| neighbor.id == id)
+        });
+
+        let id = image.id;
+        let metadata = Task::perform(image.clone().metadata(), move |result| {
+            Message::MetadataLoaded(id, result)
+        });
+
+        let size = if self.viewer.is_actual_size() {
+            Size::Actual
+        } else {
+            Size::Original
+        };
+
+        let preloaded = self.viewer.preloaded(id).filter(|rgba| {
+            size == Size::Original || !is_capped(rgba, &image)
+        });
+
+        let current = if let Some(rgba) = preloaded {
+            self.viewer.show(rgba.clone());
+            histogram(id, rgba)
+        } else {
+            Task::perform(image.download(size), move |result| {
+                Message::ImageDownloaded(id, result)
+            })
+        };
+
+        let preloads = neighbors
+            .into_iter()
+            .filter(|neighbor| !self.viewer.is_preloaded(neighbor.id))
+            .map(|neighbor| {
+                let id = neighbor.id;
+                Task::perform(neighbor.download(Size::Original), move |result| {
+                    Message::ImagePreloaded(id, result)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let filmstrip = self.filmstrip();
+        let centered = filmstrip
+            .iter()
+            .position(|(candidate, _)| *candidate == id)
+            .map_or_else(Task::none, |position| {
+                Viewer::center_filmstrip(
+                    position,
+                    filmstrip.len(),
+                    self.window_size.width,
+                )
+            });
+
+        Task::batch(
+            [current, metadata, centered].into_iter().chain(preloads),
+        )
+    }
+
+    /// Drops the failure of the image `id`, once it decodes again or is
+    /// decoded anew.
+    fn forget_failure(&mut self, id: Id) {
+        if self.failures.is_empty() {
+            return;
+        }
+
+        if let Some(position) = self.position(id) {
+            let path = self.images[position].path();
+
+            self.failures.retain(|(failed, _)| failed != path);
+        }
+    }
+
+    /// Forgets the image `id` after its file is gone, moving the viewer on
+    /// to the next image if it was showing it.
+    fn remove(&mut self, id: Id) -> Task<Message> {
+        let Some(index) = self.position(id) else {
+            return Task::none();
+        };
+
+        self.forget_failure(id);
+        self.ids.forget(id);
+
+        let _ = self.images.remove(index);
+        let _ = self.previews.remove(&id);
+        let _ = self.details.remove(&id);
+        let _ = self.selected_ids.remove(&id);
+        let _ = self.favorites.remove(&id);
+
+        if self.viewer.compared_id() == Some(id) {
+            self.viewer.close();
+        } else if self.viewer.current_id() == Some(id) {
+            let end = self.page_range().end;
+
+            let next = self.images[index.min(end)..end]
+                .iter()
+                .position(|image| self.is_visible(image))
+                .map(|offset| index + offset)
+                .or_else(|| self.previous_visible(index));
+
+            match next {
+                Some(next) => return self.go_to(next),
+                None => self.viewer.close(),
+            }
+        } else if let Some((current_index, _)) = self.viewer.current() {
+            if current_index > index {
+                self.viewer.reindex(current_index - 1);
+            }
+        }
+        Task::none()
+    }
+
+    /// Decodes the current image again at its native resolution, if it was
+    /// scaled down and is now shown at its actual size.
+    fn load_actual_size(&self) -> Task<Message> {
+        if !self.viewer.is_actual_size() {
+            return Task::none();
+        }
+
+        let Some(image) = self.viewer.current_id().and_then(|id| {
+            self.images.iter().find(|image| image.id == id)
+        }) else {
+            return Task::none();
+        };
+
+        let is_capped = self
+            .viewer
+            .current_image()
+            .is_some_and(|rgba| is_capped(&rgba, image));
+
+        if !is_capped {
+            return Task::none();
+        }
+
+        let id = image.id;
+
+        Task::perform(image.clone().download(Size::Actual), move |result| {
+            Message::ImageDownloaded(id, result)
+        })
+    }
+
+    /// The current and new path of every selected image, in the order of
+    /// the grid.
+    fn rename_targets(
+        &self,
+        template: &str,
+    ) -> Result<Vec<(Id, PathBuf, PathBuf)>, TemplateError> {
+        let selected: Vec<&ImageData> = self
+            .images
+            .iter()
+            .filter(|image| self.selected_ids.contains(&image.id))
+            .collect();
+
+        let targets = rename::targets(
+            template,
+            selected.iter().map(|image| image.path()),
+        )?;
+
+        Ok(selected
+            .into_iter()
+            .zip(targets)
+            .map(|(image, target)| {
+                (image.id, image.path().to_path_buf(), target)
+            })
+            .collect())
+    }
+
+    /// Sorts the images in the chosen order, keeping the viewer on the same
+    /// image.
+    fn sort(&mut self) {
+        let order = self.sort_order;
+        self.images.sort_by(|a, b| order.compare(a, b));
+
+        let current = self.viewer.current();
+
+        if let Some(index) = current.and_then(|(_, id)| self.position(id)) {
+            self.viewer.reindex(index);
+        }
+    }
+
+    /// Drops every thumbnail, along with the ones still decoding, so they
+    /// are decoded anew once their card is shown.
+    fn discard_thumbnails(&mut self) {
+        self.previews.clear();
+        self.thumbnails_requested = 0;
+        self.thumbnails_decoded = 0;
+        self.thumbnail_generation = self.thumbnail_generation.wrapping_add(1);
+    }
+
+    /// Forgets every image, closing the viewer, before other ones are
+    /// listed.
+    fn clear(&mut self) {
+        self.images.clear();
+        self.ids.clear();
+        self.failures.clear();
+        self.discard_thumbnails();
+        self.details.clear();
+        self.selected_ids.clear();
+        self.favorites.clear();
+        self.selection_anchor = None;
+        self.focused_index = None;
+        self.page = 0;
+        self.last_seen.clear();
+        self.on_screen.clear();
+        self.image_dirs.clear();
+        self.unreadable_dirs.clear();
+        self.loaded = false;
+        self.viewer.close();
+    }
+
+    /// The image files of a scan, reporting the directories that could
+    /// not be read.
+    fn take_scan(
+        &mut self,
+        (paths, unreadable): helper::Scanned,
+    ) -> Vec<PathBuf> {
+        for (dir, error) in unreadable {
+            self.toast = Some(Toast::error(format!(
+                "Failed to open {}: {error}",
+                dir.display()
+            )));
+            self.unreadable_dirs.push((dir, error));
+        }
+
+        paths
+    }
+
+    /// Keeps the first of `paths` in the sort order, so no more than the
+    /// limit are listed next to the `listed` images, noting how many were
+    /// found.
+    fn cap(&mut self, paths: Vec<PathBuf>, listed: usize) -> Vec<PathBuf> {
+        let Some(limit) = self.limit else {
+            return paths;
+        };
+
+        let room = limit.saturating_sub(listed);
+
+        if paths.len() > room || self.found.is_some() {
+            self.found = Some(self.found.unwrap_or(listed) + paths.len());
+        }
+
+        self.first_in_order(paths, room)
+    }
+
+    /// The first `count` of `paths` in the sort order.
+    fn first_in_order(
+        &self,
+        mut paths: Vec<PathBuf>,
+        count: usize,
+    ) -> Vec<PathBuf> {
+        if paths.len() > count {
+            let order = self.sort_order;
+
+            paths.sort_by(|a, b| {
+                order.compare_names(&file_name(a), &file_name(b))
+            });
+            paths.truncate(count);
+        }
+
+        paths
+    }
+
+    fn position(&self, id: Id) -> Option<usize> {
+        self.images.iter().position(|image| image.id == id)
+    }
+
+    fn file_name(&self, id: Id) -> String {
+        self.images
+            .iter()
+            .find(|image| image.id == id)
+            .map(ImageData::file_name)
+            .unwrap_or_default()
+    }
+
+    fn is_neighbor(&self, id: Id) -> bool {
+        let Some((current_index, _)) = self.viewer.current() else {
+            return false;
+        };
+
+        [
+            Some(current_index),
+            self.previous_visible(current_index),
+            self.next_visible(current_index),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|index| self.images[index].id == id)
+    }
+
+    /// Whether the file name of the image contains the filter query,
+    /// ignoring case, it is a favorite when only those are shown, and it
+    /// has the minimum rating. Empty files are skipped unless they are
+    /// shown as incomplete.
+    fn is_visible(&self, image: &ImageData) -> bool {
+        if image.is_incomplete() && !self.show_incomplete {
+            return false;
+        }
+
+        let is_match = self.filter.is_empty()
+            || image
+                .file_name()
+                .to_lowercase()
+                .contains(&self.filter.to_lowercase());
+
+        is_match
+            && (!self.favorites_only || self.favorites.contains(&image.id))
+            && self.minimum_rating.allows(self.rating(image))
+    }
+
+    /// The stars given to the image, or 0 when it is unrated.
+    fn rating(&self, image: &ImageData) -> u8 {
+        self.ratings.get(image.path()).copied().unwrap_or_default()
+    }
+
+    /// The index of the closest image before `index` on the current page
+    /// that matches the filter.
+    fn previous_visible(&self, index: usize) -> Option<usize> {
+        let range = self.page_range();
+
+        self.images[range.start..index.clamp(range.start, range.end)]
+            .iter()
+            .rposition(|image| self.is_visible(image))
+            .map(|offset| range.start + offset)
+    }
+
+    /// The index of the closest image after `index` on the current page
+    /// that matches the filter.
+    fn next_visible(&self, index: usize) -> Option<usize> {
+        self.images
+            .iter()
+            .enumerate()
+            .take(self.page_range().end)
+            .skip(index + 1)
+            .find(|(_, image)| self.is_visible(image))
+            .map(|(index, _)| index)
+    }
+
+    /// The range of `images` on the current page, or on the last page once
+    /// there are fewer images.
+    fn page_range(&self) -> Range<usize> {
+        let page = self.page.min(self.page_count() - 1);
+        let start = page * ImageData::LIMIT;
+
+        start..(start + ImageData::LIMIT).min(self.images.len())
+    }
+
+    fn page_count(&self) -> usize {
+        self.images.len().div_ceil(ImageData::LIMIT).max(1)
+    }
+
+    /// The images on the current page, including the ones hidden by the
+    /// filter.
+    fn page_images(&self) -> &[ImageData] {
+        &self.images[self.page_range()]
+    }
+
+    /// Switches to another page, unloading the thumbnails of the previous
+    /// one.
+    fn show_page(&mut self, page: usize) {
+        self.page = page.min(self.page_count() - 1);
+        self.previews.clear();
+        self.last_seen.clear();
+        self.on_screen.clear();
+        self.focused_index = None;
+    }
+
+    /// The index of the image a page away from `index`, stopping at the
+    /// first or last image that matches the filter.
+    fn page(&self, index: usize, forward: bool) -> Option<usize> {
+        let mut target = None;
+
+        for _ in 0..self.page_stride {
+            let current = target.unwrap_or(index);
+            let step = if forward {
+                self.next_visible(current)
+            } else {
+                self.previous_visible(current)
+            };
+
+            match step {
+                Some(step) => target = Some(step),
+                None => break,
+            }
+        }
+
+        target
+    }
+
+    /// The first image in the topmost row on screen. Masonry columns have
+    /// no rows, so nothing peeks while scrolling them.
+    fn topmost(&self, viewport: &Viewport) -> Option<Id> {
+        if self.layout == Layout::Masonry {
+            return None;
+        }
+
+        let stride = self.card_size() as f32 + self.spacing.pixels();
+        let first_row = (viewport.absolute_offset().y / stride) as usize;
+
+        self.page_images()
+            .iter()
+            .filter(|image| self.is_visible(image))
+            .nth(first_row * self.grid_columns())
+            .map(|image| image.id)
+    }
+
+    fn is_peeking(&self) -> bool {
+        self.peek.is_some_and(|(_, scrolled_at)| {
+            self.now.saturating_duration_since(scrolled_at)
+                < Self::PEEK_DURATION
+        })
+    }
+
+    /// The thumbnail and name of the topmost image, following the scrollbar
+    /// while the grid is scrolled.
+    fn peek(&self) -> Option<Element<'_, Message>> {
+        if !self.is_peeking() || self.viewer.is_open(self.now) {
+            return None;
+        }
+
+        let (id, _) = self.peek?;
+        let (_, height) = self.grid_viewport?;
+
+        let thumbnail: Element<'_, _> = match self.previews.get(&id) {
+            Some(Preview::Ready { thumbnail }) => {
+                iced::widget::image(thumbnail.handle.clone())
+                    .width(Self::PEEK_SIZE)
+                    .height(Self::PEEK_SIZE)
+                    .into()
+            }
+            _ => horizontal_space()
+                .width(Self::PEEK_SIZE)
+                .height(Self::PEEK_SIZE)
+                .into(),
+        };
+
+        let card = container(
+            column![thumbnail, text(self.file_name(id)).size(12)]
+                .spacing(5)
+                .align_x(Center)
+                .width(Self::PEEK_SIZE),
+        )
+        .padding(5)
+        .style(container::rounded_box);
+
+        // Roughly where the scroller is, keeping the card on screen
+        let room = (height - Self::PEEK_SIZE as f32 - 40.0).max(0.0);
+
+        Some(
+            container(card)
+                .align_right(Fill)
+                .height(Fill)
+                .padding(Padding {
+                    top: Self::MARGIN + self.scroll_offset.y * room,
+                    right: 2.0 * Self::MARGIN + Self::SCROLLBAR_WIDTH,
+                    ..Padding::ZERO
+                })
+                .into(),
+        )
+    }
+
+    /// The floating button scrolling the grid back to the top, fading in
+    /// and out.
+    fn back_to_top(&self) -> Option<Element<'_, Message>> {
+        let opacity = self.back_to_top.interpolate(0.0, 1.0, self.now);
+
+        if opacity <= 0.0 {
+            return None;
+        }
+
+        let button = button(text("↑ Back to top"))
+            .on_press(Message::ScrollToTop)
+            .padding([8, 16])
+            .style(move |theme, status| {
+                let style = button::primary(theme, status);
+
+                button::Style {
+                    background: style
+                        .background
+                        .map(|background| background.scale_alpha(opacity)),
+                    text_color: style.text_color.scale_alpha(opacity),
+                    ..style
+                }
+            });
+
+        Some(
+            container(button)
+                .align_right(Fill)
+                .align_bottom(Fill)
+                .padding(Padding {
+                    bottom: 4.0 * Self::MARGIN,
+                    right: 4.0 * Self::MARGIN + Self::SCROLLBAR_WIDTH,
+                    ..Padding::ZERO
+                })
+                .into(),
+        )
+    }
+
+    /// Whether placeholder cards are shown or any thumbnail is decoding.
+    fn is_shimmering(&self) -> bool {
+        let is_listing = !self.loaded && self.images.is_empty();
+        let is_decoding = self
+            .previews
+            .values()
+            .any(|preview| matches!(preview, Preview::Loading));
+
+        (is_listing || is_decoding)
+            && self.animation_speed != AnimationSpeed::Off
+    }
+
+    /// Counts a finished thumbnail, starting over once every requested
+    /// thumbnail is done so the progress bar tracks the next batch.
+    fn count_decoded_thumbnail(&mut self) {
+        self.thumbnails_decoded += 1;
+
+        if self.thumbnails_decoded >= self.thumbnails_requested {
+            self.thumbnails_requested = 0;
+            self.thumbnails_decoded = 0;
+        }
+    }
+
+    /// Marks the cards within `viewport` as seen just now.
+    fn touch_on_screen(&mut self, viewport: &Viewport) {
+        let top = viewport.absolute_offset().y;
+        let bottom = top + viewport.bounds().height;
+
+        let on_screen: HashSet<Id> = self
+            .placements()
+            .into_iter()
+            .filter(|card| card.top <= bottom && card.top + card.height >= top)
+            .map(|card| card.id)
+            .collect();
+
+        let now = Instant::now();
+
+        for id in &on_screen {
+            let _ = self.last_seen.insert(*id, now);
+        }
+
+        self.on_screen = on_screen;
+    }
+
+    /// Unloads the thumbnails unseen for the longest until the rest fit in
+    /// the memory budget. They load again once their card is shown.
+    fn evict_thumbnails(&mut self) {
+        let mut bytes: usize = self.previews.values().map(Preview::bytes).sum();
+
+        while bytes > self.memory_budget.bytes() {
+            let Some(id) = self
+                .previews
+                .iter()
+                .filter(|(id, preview)| {
+                    preview.bytes() > 0 && !self.on_screen.contains(*id)
+                })
+                .min_by_key(|(id, _)| self.last_seen.get(*id))
+                .map(|(id, _)| *id)
+            else {
+                break;
+            };
+
+            // The card requests it again once it pops back into view
+            if let Some(preview) = self.previews.remove(&id) {
+                bytes -= preview.bytes();
+            }
+        }
+    }
+
+    /// Moves the focus by `offset` cards, scrolling the grid to keep the
+    /// focused card in view.
+    fn move_focus(&mut self, offset: isize) -> Task<Message> {
+        let placements = self.placements();
+        let visible = placements.len();
+
+        if visible == 0 {
+            return Task::none();
+        }
+
+        let focused = match self.focused_index {
+            Some(index) => (index.min(visible - 1) as isize + offset)
+                .clamp(0, visible as isize - 1) as usize,
+            None => 0,
+        };
+
+        self.focused_index = Some(focused);
+
+        let Some((scrolled, height)) = self.grid_viewport else {
+            return Task::none();
+        };
+
+        let card = placements[focused];
+        let top = card.top;
+        let bottom = top + card.height + self.spacing.pixels();
+
+        let y = if top < scrolled {
+            top
+        } else if bottom > scrolled + height {
+            bottom - height
+        } else {
+            return Task::none();
+        };
+
+        scrollable::scroll_to(GRID.clone(), AbsoluteOffset { x: 0.0, y })
+    }
+
+    /// The offset to the card above or below the focused one. Masonry
+    /// lanes have no rows, so it is the next card in the same lane there.
+    fn vertical_offset(&self, is_down: bool) -> isize {
+        let columns = self.grid_columns() as isize;
+
+        if self.layout == Layout::Grid {
+            return if is_down { columns } else { -columns };
+        }
+
+        let placements = self.placements();
+
+        let Some(focused) = self
+            .focused_index
+            .map(|index| index.min(placements.len().saturating_sub(1)))
+        else {
+            return 0;
+        };
+        let Some(lane) = placements.get(focused).map(|card| card.lane) else {
+            return 0;
+        };
+
+        let target = if is_down {
+            (focused + 1..placements.len())
+                .find(|index| placements[*index].lane == lane)
+        } else {
+            (0..focused).rev().find(|index| placements[*index].lane == lane)
+        };
+
+        target.map_or(0, |target| target as isize - focused as isize)
+    }
+
+    fn focused_id(&self) -> Option<Id> {
+        self.page_images()
+            .iter()
+            .filter(|image| self.is_visible(image))
+            .nth(self.focused_index?)
+            .map(|image| image.id)
+    }
+
+    /// The amount of cards in each row of the grid.
+    fn grid_columns(&self) -> usize {
+        match self.columns {
+            Columns::Fixed(count) => usize::from(count),
+            Columns::Auto => {
+                let spacing = self.spacing.pixels();
+                let stride = self.card_size() as f32 + spacing;
+                let available = self.window_size.width
+                    - 2.0 * spacing
+                    - Self::SCROLLBAR_WIDTH
+                    + spacing;
+
+                (available / stride).max(1.0) as usize
+            }
+        }
+    }
+
+    /// The width and height of the card of `image`. Masonry cards keep the
+    /// aspect ratio of their image, within reason for panoramas.
+    fn card_dimensions(&self, image: &ImageData) -> (u32, u32) {
+        let width = self.card_size();
+
+        match (self.layout, image.dimensions()) {
+            (Layout::Masonry, Some((image_width, image_height)))
+                if image_width > 0 && image_height > 0 =>
+            {
+                let height = u64::from(width) * u64::from(image_height)
+                    / u64::from(image_width);
+
+                (width, (height as u32).clamp(width / 4, width * 4).max(1))
+            }
+            _ => (width, width),
+        }
+    }
+
+    fn card<'a>(
+        &'a self,
+        image: &'a ImageData,
+        focused_id: Option<Id>,
+        shimmer: f32,
+    ) -> Element<'a, Message> {
+        let (width, height) = self.card_dimensions(image);
+
+        card(
+            image,
+            self.previews.get(&image.id),
+            self.details.get(&image.id),
+            width,
+            height,
+            self.thumbnail_fit,
+            self.selected_ids.contains(&image.id),
+            focused_id == Some(image.id),
+            self.favorites.contains(&image.id),
+            self.rating(image),
+            shimmer,
+            self.now,
+        )
+    }
+
+    /// Lays the cards out in rows, only building the ones near the
+    /// viewport. The rows further away are stood in for by empty space of
+    /// the same height, since the view is rebuilt on every animation frame
+    /// and a page may hold a thousand cards.
+    fn grid(
+        &self,
+        focused_id: Option<Id>,
+        shimmer: f32,
+    ) -> Element<'_, Message> {
+        let columns = self.grid_columns();
+        let spacing = self.spacing.pixels();
+        let stride = self.card_size() as f32 + spacing;
+
+        let images: Vec<&ImageData> = self
+            .page_images()
+            .iter()
+            .filter(|image| self.is_visible(image))
+            .collect();
+
+        let rows = images.len().div_ceil(columns);
+
+        let (scrolled, height) = self
+            .grid_viewport
+            .unwrap_or((0.0, self.window_size.height));
+
+        let first = ((scrolled / stride) as usize)
+            .saturating_sub(Self::OVERSCAN_ROWS)
+            .min(rows);
+        let last = (((scrolled + height) / stride).ceil() as usize
+            + Self::OVERSCAN_ROWS)
+            .clamp(first, rows);
+
+        // The spacing of the column already separates the filler from the
+        // first and last rows built
+        let filler = |skipped: usize| {
+            (skipped > 0).then(|| {
+                vertical_space().height(skipped as f32 * stride - spacing)
+            })
+        };
+
+        let built = images.chunks(columns).take(last).skip(first).map(|chunk| {
+            let cards = chunk
+                .iter()
+                .map(|image| self.card(image, focused_id, shimmer));
+
+            (
+                GridRow::Cards(chunk[0].id),
+                Element::from(row(cards).spacing(spacing)),
+            )
+        });
+
+        keyed_column(
+            filler(first)
+                .map(|filler| (GridRow::Above, Element::from(filler)))
+                .into_iter()
+                .chain(built)
+                .chain(
+                    filler(rows - last)
+                        .map(|filler| (GridRow::Below, Element::from(filler))),
+                ),
+        )
+        .spacing(spacing)
+        .into()
+    }
+
+    /// Selects the cards of the grid that a rectangle dragged from a press
+    /// over it touches.
+    fn drag_rubber_band(&mut self, event: mouse::Event) {
+        match event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                let is_blocked = self.context_menu.is_some()
+                    || self.rename.is_some()
+                    || self.is_help_visible
+                    || self.layout != Layout::Grid;
+
+                if is_blocked {
+                    return;
+                }
+
+                self.rubber_band =
+                    self.content_position(self.cursor).map(|origin| {
+                        RubberBand {
+                            origin,
+                            current: origin,
+                            base: None,
+                        }
+                    });
+            }
+            mouse::Event::CursorMoved { position } => {
+                let Some(current) = self.content_position(position) else {
+                    return;
+                };
+                let Some(band) = &mut self.rubber_band else {
+                    return;
+                };
+
+                band.current = current;
+
+                if band.base.is_none()
+                    && band.origin.distance(current) > Self::DRAG_THRESHOLD
+                {
+                    band.base = Some(self.selected_ids.clone());
+                }
+
+                let (Some(mut selected), bounds) =
+                    (band.base.clone(), band.bounds())
+                else {
+                    return;
+                };
+
+                selected.extend(self.cards_within(bounds));
+                self.selected_ids = selected;
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) => {
+                self.rubber_band = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Converts a `position` in the window into the coordinates of the
+    /// scrolled content of the grid. Only the area of the cards, without
+    /// the scrollbar, has such a position.
+    fn content_position(&self, position: Point) -> Option<Point> {
+        let bounds = self.grid_bounds?;
+        let (scrolled, _) = self.grid_viewport?;
+
+        let cards = Rectangle {
+            width: bounds.width - Self::SCROLLBAR_WIDTH,
+            ..bounds
+        };
+
+        if !cards.contains(position) && self.rubber_band.is_none() {
+            return None;
+        }
+
+        Some(Point::new(
+            position.x - bounds.x,
+            position.y - bounds.y + scrolled,
+        ))
+    }
+
+    /// The cards of the grid intersecting `area`, in the coordinates of its
+    /// scrolled content.
+    fn cards_within(&self, area: Rectangle) -> Vec<Id> {
+        let Some(bounds) = self.grid_bounds else {
+            return Vec::new();
+        };
+
+        let columns = self.grid_columns();
+        let spacing = self.spacing.pixels();
+        let size = self.card_size() as f32;
+        let stride = size + spacing;
+
+        // The rows are centered within the space left of the scrollbar
+        let available = bounds.width - Self::SCROLLBAR_WIDTH - spacing;
+        let width = columns as f32 * stride - spacing;
+        let left = ((available - width) / 2.0).max(0.0);
+
+        self.page_images()
+            .iter()
+            .filter(|image| self.is_visible(image))
+            .enumerate()
+            .filter(|(index, _)| {
+                let card = Rectangle {
+                    x: left + (index % columns) as f32 * stride,
+                    y: (index / columns) as f32 * stride,
+                    width: size,
+                    height: size,
+                };
+
+                card.intersects(&area)
+            })
+            .map(|(_, image)| image.id)
+            .collect()
+    }
+
+    /// The images around the current one in the viewer, with their
+    /// thumbnails if they are decoded.
+    fn filmstrip(&self) -> Vec<(Id, Option<&iced::widget::image::Handle>)> {
+        let Some(current_id) = self.viewer.current_id() else {
+            return Vec::new();
+        };
+
+        let visible: Vec<&ImageData> = self
+            .page_images()
+            .iter()
+            .filter(|image| self.is_visible(image))
+            .collect();
+
+        let Some(current) =
+            visible.iter().position(|image| image.id == current_id)
+        else {
+            return Vec::new();
+        };
+
+        let start = current.saturating_sub(Self::FILMSTRIP_REACH);
+        let end = (current + Self::FILMSTRIP_REACH + 1).min(visible.len());
+
+        visible[start..end]
+            .iter()
+            .map(|image| {
+                let handle = match self.previews.get(&image.id) {
+                    Some(Preview::Ready { thumbnail }) => {
+                        Some(&thumbnail.handle)
                     }
+                    _ => None,
+                };
+
+                (image.id, handle)
+            })
+            .collect()
+    }
+
+    /// The count of the changed pixels and the amplification of the
+    /// difference shown in the viewer, once it is computed.
+    fn diff_controls(&self) -> Option<Element<'_, Message>> {
+        let mode = self.diff.as_ref()?;
+        let diff = mode.diff.as_ref()?;
+
+        if !self.viewer.is_open(self.now) {
+            return None;
+        }
+
+        let total = u64::from(diff.rgba.width) * u64::from(diff.rgba.height);
+
+        let changed = text(format!(
+            "{} of {total} pixels changed ({:.2}%)",
+            diff.changed,
+            100.0 * diff.changed as f64 / total.max(1) as f64,
+        ));
+
+        let amplification = row![
+            text("Amplify"),
+            slider(
+                1..=Self::MAX_AMPLIFICATION,
+                mode.amplification,
+                Message::DiffAmplificationChanged,
+            )
+            .on_release(Message::AmplifyDiff)
+            .width(160),
+            text(format!("×{}", mode.amplification)),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        Some(
+            container(
+                container(column![changed, amplification].spacing(5))
+                    .padding(10)
+                    .style(container::rounded_box),
+            )
+            .center_x(Fill)
+            .align_top(Fill)
+            .padding(4.0 * Self::MARGIN)
+            .into(),
+        )
+    }
+
+    /// The selection rectangle while it is being dragged, clipped to the
+    /// grid.
+    fn rubber_band(&self) -> Option<Element<'_, Message>> {
+        let band = self.rubber_band.as_ref()?;
+        band.base.as_ref()?;
+
+        let bounds = self.grid_bounds?;
+        let (scrolled, _) = self.grid_viewport?;
+
+        let area = band.bounds();
+        let area = Rectangle {
+            x: area.x + bounds.x,
+            y: area.y + bounds.y - scrolled,
+            ..area
+        }
+        .intersection(&bounds)?;
+
+        let rectangle = container(horizontal_space())
+            .width(area.width)
+            .height(area.height)
+            .style(|theme: &Theme| {
+                let color = theme.extended_palette().primary.base.color;
+
+                container::Style {
+                    background: Some(color.scale_alpha(0.2).into()),
+                    border: Border {
+                        color,
+                        width: 1.0,
+                        radius: 0.0.into(),
+                    },
+                    ..container::Style::default()
                 }
-                Task::none()
-            }
-            Message::ImagesListed(Err(error))
-            | Message::ImageDownloaded(Err(error))
-            | Message::ThumbnailDownloaded(_, Err(error)) => {
-                dbg!(error);
-                Task::none()
+            });
+
+        Some(
+            container(rectangle)
+                .padding(Padding {
+                    top: area.y,
+                    left: area.x,
+                    ..Padding::ZERO
+                })
+                .into(),
+        )
+    }
+
+    /// Places every card into the column that is the shortest so far.
+    fn masonry(
+        &self,
+        focused_id: Option<Id>,
+        shimmer: f32,
+    ) -> Element<'_, Message> {
+        let spacing = self.spacing.pixels();
+        let mut lanes: Vec<Vec<Element<'_, _>>> =
+            (0..self.grid_columns()).map(|_| Vec::new()).collect();
+
+        let images = self
+            .page_images()
+            .iter()
+            .filter(|image| self.is_visible(image));
+
+        for (image, placement) in images.zip(self.placements()) {
+            lanes[placement.lane].push(self.card(image, focused_id, shimmer));
+        }
+
+        row(lanes
+            .into_iter()
+            .map(|lane| column(lane).spacing(spacing).into()))
+        .spacing(spacing)
+        .into()
+    }
+
+    /// Where every visible card of the page is laid out, in order. Grid
+    /// cards fill the rows in turn, while masonry cards go into the lane
+    /// that is the shortest so far.
+    fn placements(&self) -> Vec<Placement> {
+        let columns = self.grid_columns();
+        let spacing = self.spacing.pixels();
+        let mut heights = vec![0.0; columns];
+
+        self.page_images()
+            .iter()
+            .filter(|image| self.is_visible(image))
+            .enumerate()
+            .map(|(index, image)| {
+                let (_, height) = self.card_dimensions(image);
+                let height = height as f32;
+
+                let lane = match self.layout {
+                    Layout::Grid => index % columns,
+                    Layout::Masonry => (0..columns)
+                        .min_by(|a, b| heights[*a].total_cmp(&heights[*b]))
+                        .unwrap_or_default(),
+                };
+
+                let top = heights[lane];
+                heights[lane] += height + spacing;
+
+                Placement {
+                    id: image.id,
+                    lane,
+                    top,
+                    height,
+                }
+            })
+            .collect()
+    }
+
+    /// The side length of each card, fitting the chosen amount of columns
+    /// into the current window width.
+    fn card_size(&self) -> u32 {
+        match self.columns {
+            Columns::Auto => self.thumbnail_size.pixels(),
+            Columns::Fixed(count) => {
+                let count = f32::from(count);
+                let spacing = self.spacing.pixels();
+                let available = self.window_size.width
+                    - 2.0 * spacing
+                    - Self::SCROLLBAR_WIDTH
+                    - spacing * count;
+
+                (available / count).max(1.0) as u32
             }
         }
     }
 
+    /// How many thumbnails failed to load, expanding into the list of the
+    /// files and their errors when pressed.
+    fn failures(&self) -> Option<Element<'_, Message>> {
+        if self.failures.is_empty() {
+            return None;
+        }
+
+        let summary = button(text(format!(
+            "{} {} of {} failed to load",
+            if self.is_failures_expanded { "▾" } else { "▸" },
+            self.failures.len(),
+            self.images.len()
+        )))
+        .on_press(Message::FailuresToggled)
+        .style(button::text);
+
+        let list = self.is_failures_expanded.then(|| {
+            scrollable(
+                column(self.failures.iter().map(|(path, error)| {
+                    row![
+                        text(file_name(path)).width(Fill),
+                        text(error.to_string()).width(Fill),
+                    ]
+                    .spacing(10)
+                    .into()
+                }))
+                .spacing(5),
+            )
+            .height(Self::FAILURES_HEIGHT)
+        });
+
+        Some(
+            column![summary]
+                .push_maybe(list)
+                .padding([0.0, self.spacing.pixels()])
+                .into(),
+        )
+    }
+
+    /// Notes that only the first images found were listed, because of the
+    /// limit given on the command line.
+    fn truncation(&self) -> Option<Element<'_, Message>> {
+        let found = self.found?;
+
+        Some(
+            container(
+                text(format!(
+                    "Showing the first {} of {found} images",
+                    self.images.len()
+                ))
+                .size(14),
+            )
+            .padding([0.0, self.spacing.pixels()])
+            .into(),
+        )
+    }
+
+    /// The controls moving between the pages of the grid, once there is
+    /// more than one.
+    fn pagination(&self) -> Option<Element<'_, Message>> {
+        let count = self.page_count();
+
+        if count < 2 {
+            return None;
+        }
+
+        let page = self.page_range().start / ImageData::LIMIT;
+
+        Some(
+            center_x(
+                row![
+                    button(text("‹ Previous")).on_press_maybe(
+                        (page > 0).then(|| Message::PageChanged(page - 1)),
+                    ),
+                    text(format!("Page {} of {count}", page + 1)),
+                    button(text("Next ›")).on_press_maybe(
+                        (page + 1 < count)
+                            .then(|| Message::PageChanged(page + 1)),
+                    ),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            )
+            .padding(10)
+            .into(),
+        )
+    }
+
+    fn toolbar(&self) -> Element<'_, Message> {
+        row![
+            text_input("Filter by name…", &self.filter)
+                .on_input(Message::FilterChanged)
+                .width(200),
+            checkbox("Favorites only", self.favorites_only)
+                .on_toggle(Message::FavoritesOnlyToggled),
+            pick_list(
+                MinimumRating::ALL,
+                Some(self.minimum_rating),
+                Message::MinimumRatingChanged,
+            ),
+            checkbox("Show incomplete files", self.show_incomplete)
+                .on_toggle(Message::ShowIncompleteToggled),
+            checkbox("Watch folders", self.is_watching)
+                .on_toggle(Message::WatchToggled),
+            text("Thumbnails"),
+            pick_list(
+                ThumbnailSize::ALL,
+                Some(self.thumbnail_size),
+                Message::ThumbnailSizeChanged,
+            ),
+            pick_list(
+                ThumbnailFit::ALL,
+                Some(self.thumbnail_fit),
+                Message::ThumbnailFitChanged,
+            ),
+            pick_list(
+                ResizeFilter::ALL,
+                Some(self.resize_filter),
+                Message::ResizeFilterChanged,
+            ),
+            text("Save as"),
+            pick_list(
+                ExportFormat::ALL,
+                Some(self.export_format),
+                Message::ExportFormatChanged,
+            ),
+            text("Layout"),
+            pick_list(Layout::ALL, Some(self.layout), Message::LayoutChanged),
+            text("Spacing"),
+            pick_list(
+                Spacing::ALL,
+                Some(self.spacing),
+                Message::SpacingChanged,
+            ),
+            text("Columns"),
+            pick_list(
+                Columns::ALL,
+                Some(self.columns),
+                Message::ColumnCountChanged,
+            ),
+            text("Sort"),
+            pick_list(
+                SortOrder::ALL,
+                Some(self.sort_order),
+                Message::SortOrderChanged,
+            ),
+            text("Page by"),
+            pick_list(
+                Self::PAGE_STRIDES,
+                Some(self.page_stride),
+                Message::PageStrideChanged,
+            ),
+            text("Animations"),
+            pick_list(
+                AnimationSpeed::ALL,
+                Some(self.animation_speed),
+                Message::AnimationSpeedChanged,
+            ),
+            checkbox("Reduce motion", self.reduce_motion)
+                .on_toggle(Message::ReduceMotionToggled),
+            text("Memory"),
+            pick_list(
+                MemoryBudget::ALL,
+                Some(self.memory_budget),
+                Message::MemoryBudgetChanged,
+            ),
+            text("Theme"),
+            pick_list(
+                ThemeChoice::all(),
+                Some(
+                    self.theme
+                        .clone()
+                        .map_or(ThemeChoice::System, ThemeChoice::Theme),
+                ),
+                Message::ThemeChanged,
+            ),
+            horizontal_space(),
+            button(text("Regenerate thumbnails"))
+                .on_press(Message::RegenerateThumbnails),
+            button(text("Rename selected…")).on_press_maybe(
+                (!self.selected_ids.is_empty())
+                    .then_some(Message::RenameSelected),
+            ),
+            self.copy_status(),
+        ]
+        .spacing(10)
+        .padding(4)
+        .align_y(Center)
+        .into()
+    }
+
+    fn copy_status(&self) -> Element<'_, Message> {
+        if let Some(progress) = &self.copy_progress {
+            let done = progress.done();
+
+            row![
+                text(format!("Copying {done} / {}", progress.total)),
+                progress_bar(0.0..=progress.total as f32, done as f32)
+                    .width(120)
+                    .height(8),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        } else {
+            let selected = self.selected_ids.len();
+
+            button(text(format!("Copy {selected} selected to folder…")))
+                .on_press_maybe((selected > 0).then_some(Message::CopySelected))
+                .into()
+        }
+    }
+
+    /// The thumbnails loaded and the full images held by the viewer, with
+    /// the memory they take up, to help pick a memory budget.
+    fn status_bar(&self) -> Element<'_, Message> {
+        let (thumbnails, bytes) = self
+            .previews
+            .values()
+            .map(Preview::bytes)
+            .filter(|bytes| *bytes > 0)
+            .fold((0, 0), |(count, total), bytes| (count + 1, total + bytes));
+
+        let budget = match self.memory_budget {
+            MemoryBudget::Unlimited => String::new(),
+            budget => format!(" of {budget}"),
+        };
+
+        let viewer = match self.viewer.image_bytes() {
+            Some(bytes) => format!(
+                "Viewer holds a full image of {}",
+                helper::format_bytes(bytes as u64)
+            ),
+            None => String::from("Viewer holds no full image"),
+        };
+
+        let preloaded = match self.viewer.preloaded_bytes() {
+            0 => String::new(),
+            bytes => format!(
+                ", {} preloaded",
+                helper::format_bytes(bytes as u64)
+            ),
+        };
+
+        container(
+            row![
+                text(format!(
+                    "{thumbnails} thumbnails loaded, {}{budget}",
+                    helper::format_bytes(bytes as u64)
+                ))
+                .size(12),
+                horizontal_space(),
+                text(format!("{viewer}{preloaded}")).size(12),
+            ]
+            .spacing(10),
+        )
+        .padding([2, 10])
+        .style(|theme: &Theme| container::Style {
+            text_color: Some(theme.extended_palette().background.weak.text),
+            ..container::Style::default()
+        })
+        .into()
+    }
+
+    /// Renders the toolbar and the grid, with the viewer on top while an
+    /// image is open. Fills all the space it is given.
     pub fn view(&self) -> Element<'_, Message> {
-        let gallery = if self.images.is_empty() {
-            row((0..=ImageData::LIMIT).map(|_| placeholder()))
+        let card_size = self.card_size();
+        let focused_id = self.focused_id();
+
+        let content: Element<'_, _> = if self.loaded && self.images.is_empty() {
+            let dirs = self
+                .image_dirs
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let is_unreadable = !self.unreadable_dirs.is_empty()
+                && self.unreadable_dirs.len() == self.image_dirs.len();
+
+            let message = if is_unreadable {
+                self.unreadable_dirs
+                    .iter()
+                    .map(|(dir, error)| {
+                        format!("Failed to open {}: {error}", dir.display())
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else {
+                format!("No images found in {dirs}")
+            };
+
+            center(text(message).size(20)).into()
         } else {
-            row(self.images.iter().map(|image| {
-                card(image, self.previews.get(&image.id), self.now)
-            }))
+            let shimmer = self.shimmer.interpolate(0.0, 1.0, self.now);
+
+            let gallery: Element<'_, _> = if self.images.is_empty() {
+                row((0..Self::PLACEHOLDER_COUNT)
+                    .map(|_| placeholder(card_size, shimmer)))
+                .spacing(self.spacing.pixels())
+                .wrap()
+                .into()
+            } else if self.layout == Layout::Masonry {
+                self.masonry(focused_id, shimmer)
+            } else {
+                self.grid(focused_id, shimmer)
+            };
+
+            let grid = container(scrollable(center_x(gallery))
+                .id(GRID.clone())
+                .spacing(self.spacing.pixels())
+                .on_scroll(Message::ViewportChanged))
+                .padding(self.spacing.pixels());
+
+            stack![grid]
+                .push_maybe(self.peek())
+                .push_maybe(self.back_to_top())
+                .into()
+        };
+
+        let progress = (self.thumbnails_requested > 0).then(|| {
+            progress_bar(
+                0.0..=self.thumbnails_requested as f32,
+                self.thumbnails_decoded as f32,
+            )
+            .height(4)
+        });
+
+        let content = column![]
+            .push_maybe(progress)
+            .push(self.toolbar())
+            .push_maybe(self.failures())
+            .push_maybe(self.truncation())
+            .push(content)
+            .push_maybe(self.pagination())
+            .push(self.status_bar());
+
+        let caption = self.viewer.current_index().and_then(|index| {
+            let image = self.images.get(index)?;
+
+            if let Some(mode) = &self.diff {
+                return Some(format!(
+                    "{} − {}",
+                    self.file_name(mode.before),
+                    self.file_name(mode.after)
+                ));
+            }
+
+            if let Some(compared) = self.viewer.compared_id() {
+                return Some(format!(
+                    "{} — {}",
+                    image.file_name(),
+                    self.file_name(compared)
+                ));
+            }
+
+            let start = self.page_range().start.min(index);
+            let position = self.images[start..index]
+                .iter()
+                .filter(|image| self.is_visible(image))
+                .count();
+            let total = self
+                .page_images()
+                .iter()
+                .filter(|image| self.is_visible(image))
+                .count();
+
+            let rating = match self.rating(image) {
+                0 => String::new(),
+                rating => format!(" {}", ratings::stars(rating)),
+            };
+
+            let caption = format!(
+                "{}{rating} — {} / {}",
+                image.file_name(),
+                position + 1,
+                total
+            );
+
+            Some(if self.viewer.pending_rotation().is_some() {
+                format!("{caption} — press Enter to save the rotation")
+            } else {
+                caption
+            })
+        });
+
+        let viewer = self.viewer.view(self.now, caption, self.filmstrip());
+
+        let context_menu = self.context_menu.map(|(id, position)| {
+            context_menu(
+                id,
+                position,
+                self.favorites.contains(&id),
+                self.window_size,
+            )
+        });
+
+        let rename = self.rename.as_ref().map(|dialog| {
+            let names = self.rename_targets(&dialog.template).map(|renames| {
+                renames
+                    .iter()
+                    .map(|(_, source, target)| {
+                        (file_name(source), file_name(target))
+                    })
+                    .collect()
+            });
+
+            rename_dialog(&dialog.template, names, dialog.is_renaming)
+        });
+
+        let toast = self.toast.as_ref().map(Toast::view);
+
+        let overlays = stack![content, viewer].push_maybe(self.diff_controls());
+
+        #[cfg(feature = "metrics")]
+        let overlays = overlays.push_maybe(self.metrics());
+
+        overlays
+            .push_maybe(self.rubber_band())
+            .push_maybe(context_menu)
+            .push_maybe(rename)
+            .push_maybe(self.is_help_visible.then(help))
+            .push_maybe(toast)
+            .into()
+    }
+
+    /// The timing of the thumbnails and originals decoded so far, in the
+    /// bottom left corner.
+    #[cfg(feature = "metrics")]
+    fn metrics(&self) -> Option<Element<'_, Message>> {
+        use crate::core::metrics;
+
+        if !self.is_metrics_visible {
+            return None;
+        }
+
+        let line = |label: &str, stats: Option<metrics::Stats>| {
+            let stats = stats.map_or_else(
+                || String::from("none decoded"),
+                |stats| stats.to_string(),
+            );
+
+            text(format!("{label}: {stats}")).size(12)
+        };
+
+        Some(
+            container(
+                container(column![
+                    line("Thumbnails", metrics::thumbnails()),
+                    line("Originals", metrics::originals()),
+                ])
+                .padding(8)
+                .style(container::rounded_box),
+            )
+            .align_left(Fill)
+            .align_bottom(Fill)
+            .padding(4.0 * Self::MARGIN)
+            .into(),
+        )
+    }
+} 
+
+/// Lists the images at `paths` in batches, appending each batch to the
+/// gallery as soon as it is ready.
+fn list(paths: Vec<PathBuf>, ids: &Ids) -> Task<Message> {
+    Task::run(
+        ImageData::list_in_batches(paths, ids.clone()),
+        Message::ImagesBatch,
+    )
+        .chain(Task::done(Message::ImagesListed))
+}
+
+/// Computes the histogram of the image shown in the viewer.
+fn histogram(id: Id, rgba: Rgba) -> Task<Message> {
+    Task::perform(Histogram::compute(rgba), move |result| {
+        Message::HistogramComputed(id, result)
+    })
+}
+
+/// Whether `rgba` has fewer pixels than the file of `image`, since it was
+/// scaled down when decoded.
+fn is_capped(rgba: &Rgba, image: &ImageData) -> bool {
+    image.dimensions().is_some_and(|(width, height)| {
+        u64::from(rgba.width) * u64::from(rgba.height)
+            < u64::from(width) * u64::from(height)
+    })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use iced::keyboard::key::{Code, NativeCode, Physical};
+
+    use std::io;
+
+    fn press(named: Named) -> Message {
+        // The key at the same place on a US layout, for the keys pressed
+        // by the tests
+        let physical_key = match named {
+            Named::ArrowLeft => Physical::Code(Code::ArrowLeft),
+            Named::ArrowRight => Physical::Code(Code::ArrowRight),
+            Named::ArrowUp => Physical::Code(Code::ArrowUp),
+            Named::ArrowDown => Physical::Code(Code::ArrowDown),
+            Named::Home => Physical::Code(Code::Home),
+            Named::End => Physical::Code(Code::End),
+            Named::PageUp => Physical::Code(Code::PageUp),
+            Named::PageDown => Physical::Code(Code::PageDown),
+            _ => Physical::Unidentified(NativeCode::Unidentified),
+        };
+
+        Message::KeyPressed(Event::KeyPressed {
+            key: Key::Named(named),
+            modified_key: Key::Named(named),
+            physical_key,
+            location: keyboard::Location::Standard,
+            modifiers: keyboard::Modifiers::default(),
+            text: None,
+        })
+    }
+
+    fn gallery_with(names: &[&str]) -> Gallery {
+        let (mut gallery, _) = Gallery::new(
+            Vec::new(),
+            helper::default_extensions(),
+            Settings::default(),
+            None,
+        );
+        let images = names.iter().map(|name| ImageData::from_path(*name));
+
+        let _ = gallery.update(Message::ImagesBatch(Ok(images.collect())));
+        gallery
+    }
+
+    /// Lets every running animation finish, like the fade in of the viewer.
+    fn settle(gallery: &mut Gallery) {
+        let later = Instant::now() + Duration::from_secs(5);
+
+        let _ = gallery.update(Message::Animate(later));
+    }
+
+    fn open(gallery: &mut Gallery, name: &str) {
+        let id = gallery
+            .images
+            .iter()
+            .find(|image| image.file_name() == name)
+            .map(|image| image.id)
+            .expect("the image is listed");
+
+        let _ = gallery.update(Message::Open(id));
+        settle(gallery);
+    }
+
+    fn current_name(gallery: &Gallery) -> Option<String> {
+        let (index, id) = gallery.viewer.current()?;
+        let image = &gallery.images[index];
+
+        assert_eq!(image.id, id, "the index points at the current image");
+        Some(image.file_name())
+    }
+
+    fn names(gallery: &Gallery) -> Vec<String> {
+        gallery.images.iter().map(ImageData::file_name).collect()
+    }
+
+    #[test]
+    fn listing_adds_the_images_in_natural_order() {
+        let mut gallery = gallery_with(&["img10.png", "img2.png", "img1.png"]);
+        let _ = gallery.update(Message::ImagesListed);
+
+        assert!(gallery.loaded);
+        assert_eq!(names(&gallery), ["img1.png", "img2.png", "img10.png"]);
+    }
+
+    #[test]
+    fn the_saved_sort_order_applies_to_the_first_listing() {
+        let settings = Settings {
+            sort_order: Some(SortOrder::NameDesc.key().to_owned()),
+            ..Settings::default()
+        };
+        let (mut gallery, _) = Gallery::new(
+            Vec::new(),
+            helper::default_extensions(),
+            settings,
+            None,
+        );
+        let images = ["a.png", "c.png", "b.png"].map(ImageData::from_path);
+
+        let _ = gallery.update(Message::ImagesBatch(Ok(images.to_vec())));
+
+        assert_eq!(gallery.sort_order, SortOrder::NameDesc);
+        assert_eq!(names(&gallery), ["c.png", "b.png", "a.png"]);
+    }
+
+    #[test]
+    fn the_limit_keeps_the_first_files_in_the_sort_order() {
+        let (mut gallery, _) = Gallery::new(
+            Vec::new(),
+            helper::default_extensions(),
+            Settings::default(),
+            Some(2),
+        );
+        let paths = ["img10.png", "img2.png", "img1.png"].map(PathBuf::from);
+
+        let capped = gallery.cap(paths.to_vec(), 0);
+
+        assert_eq!(capped, ["img1.png", "img2.png"].map(PathBuf::from));
+        assert_eq!(gallery.found, Some(3));
+    }
+
+    #[test]
+    fn rescans_count_the_files_left_out_by_the_limit_once() {
+        let (mut gallery, _) = Gallery::new(
+            Vec::new(),
+            helper::default_extensions(),
+            Settings::default(),
+            Some(2),
+        );
+        let paths = ["img10.png", "img2.png", "img1.png"].map(PathBuf::from);
+
+        for _ in 0..3 {
+            let _ = gallery.update(Message::Rescanned(Ok((
+                paths.to_vec(),
+                Vec::new(),
+            ))));
+        }
+
+        assert_eq!(gallery.found, Some(3));
+        assert_eq!(
+            gallery.first_in_order(paths.to_vec(), 2),
+            ["img1.png", "img2.png"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn failed_copies_are_listed_in_the_summary() {
+        let denied = || {
+            Error::from(io::Error::new(io::ErrorKind::PermissionDenied, "no"))
+        };
+        let progress = CopyProgress {
+            destination: PathBuf::from("backup"),
+            total: 8,
+            copied: 1,
+            failures: (0..7).map(|i| (format!("{i}.png"), denied())).collect(),
+        };
+
+        let summary = progress.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+
+        assert_eq!(lines[0], "Copied 1 of 8 images to backup");
+        assert!(lines[1].starts_with("0.png: "));
+        assert_eq!(lines.len(), 1 + CopyProgress::LISTED_FAILURES + 1);
+        assert_eq!(lines.last(), Some(&"and 2 more"));
+    }
+
+    #[test]
+    fn arrow_keys_follow_the_lanes_of_the_masonry() {
+        let (mut gallery, _) = Gallery::new(
+            Vec::new(),
+            helper::default_extensions(),
+            Settings::default(),
+            None,
+        );
+        let images = vec![
+            ImageData::from_path("a.png").with_dimensions((100, 400)),
+            ImageData::from_path("b.png"),
+            ImageData::from_path("c.png"),
+            ImageData::from_path("d.png"),
+        ];
+
+        let _ = gallery.update(Message::ImagesBatch(Ok(images)));
+        gallery.layout = Layout::Masonry;
+        gallery.columns = Columns::Fixed(2);
+
+        // The tall first card leaves the second lane to b and c
+        let _ = gallery.update(press(Named::ArrowRight));
+        let _ = gallery.update(press(Named::ArrowRight));
+        let _ = gallery.update(press(Named::ArrowDown));
+
+        assert_eq!(gallery.focused_index, Some(2));
+
+        let _ = gallery.update(press(Named::ArrowUp));
+
+        assert_eq!(gallery.focused_index, Some(1));
+    }
+
+    #[test]
+    fn rubber_bands_select_the_cards_under_them_once_scrolled() {
+        let mut gallery = gallery_with(&[
+            "a.png", "b.png", "c.png", "d.png", "e.png", "f.png",
+        ]);
+        gallery.window_size = iced::Size::new(430.0, 350.0);
+        gallery.spacing = Spacing::Normal;
+        gallery.columns = Columns::Fixed(2);
+        gallery.grid_bounds = Some(Rectangle {
+            x: 0.0,
+            y: 50.0,
+            width: 430.0,
+            height: 300.0,
+        });
+        gallery.grid_viewport = Some((300.0, 300.0));
+
+        let names = |ids: Vec<Id>| -> Vec<String> {
+            ids.into_iter().map(|id| gallery.file_name(id)).collect()
+        };
+
+        // The cards are 202 pixels wide and 4 apart, starting 4 pixels from
+        // the left, so this lands on the second card of the second row
+        let corner = gallery.content_position(Point::new(220.0, 60.0));
+        let area = Rectangle::new(corner.unwrap(), iced::Size::new(10.0, 60.0));
+
+        assert_eq!(corner, Some(Point::new(220.0, 310.0)));
+        assert_eq!(names(gallery.cards_within(area)), ["d.png"]);
+
+        // Across the gap between the second and third rows
+        let across = Rectangle {
+            x: 0.0,
+            y: 400.0,
+            width: 430.0,
+            height: 20.0,
+        };
+
+        assert_eq!(
+            names(gallery.cards_within(across)),
+            ["c.png", "d.png", "e.png", "f.png"]
+        );
+
+        // Left of the first column, where there are no cards
+        let gaps = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 3.0,
+            height: 600.0,
+        };
+
+        assert!(gallery.cards_within(gaps).is_empty());
+    }
+
+    #[test]
+    fn the_system_theme_can_be_picked_again() {
+        let mut gallery = gallery_with(&[]);
+
+        let _ = gallery.update(Message::ThemeChanged(ThemeChoice::Theme(
+            Theme::Dracula,
+        )));
+        assert_eq!(gallery.theme(), Some(Theme::Dracula));
+
+        let _ = gallery.update(Message::ThemeChanged(ThemeChoice::System));
+        assert_eq!(gallery.theme(), None);
+        assert_eq!(ThemeChoice::all()[0].to_string(), "System");
+    }
+
+    #[test]
+    fn rated_images_pass_the_minimum_rating() {
+        let mut gallery = gallery_with(&["a.png", "b.png", "c.png"]);
+
+        open(&mut gallery, "a.png");
+        let _ = gallery.update(Message::RateCurrent(4));
+        open(&mut gallery, "b.png");
+        let _ = gallery.update(Message::RateCurrent(2));
+        let _ = gallery.update(Message::RateCurrent(0));
+        open(&mut gallery, "c.png");
+        let _ = gallery.update(Message::RateCurrent(9));
+
+        let _ = gallery.update(Message::MinimumRatingChanged(
+            MinimumRating::Stars(3),
+        ));
+
+        let visible: Vec<String> = gallery
+            .images
+            .iter()
+            .filter(|image| gallery.is_visible(image))
+            .map(ImageData::file_name)
+            .collect();
+
+        assert_eq!(visible, ["a.png", "c.png"]);
+        assert_eq!(gallery.rating(&gallery.images[2]), ratings::MAX);
+    }
+
+    #[test]
+    fn opening_an_image_makes_it_current() {
+        let mut gallery = gallery_with(&["a.png", "b.png", "c.png"]);
+
+        open(&mut gallery, "b.png");
+
+        assert!(gallery.viewer.is_open(gallery.now));
+        assert_eq!(current_name(&gallery).as_deref(), Some("b.png"));
+    }
+
+    #[test]
+    fn arrow_keys_move_through_the_images() {
+        let mut gallery = gallery_with(&["a.png", "b.png", "c.png"]);
+
+        open(&mut gallery, "a.png");
+
+        let _ = gallery.update(press(Named::ArrowRight));
+        assert_eq!(current_name(&gallery).as_deref(), Some("b.png"));
+
+        let _ = gallery.update(press(Named::End));
+        assert_eq!(current_name(&gallery).as_deref(), Some("c.png"));
+
+        let _ = gallery.update(press(Named::ArrowLeft));
+        assert_eq!(current_name(&gallery).as_deref(), Some("b.png"));
+
+        let _ = gallery.update(press(Named::Home));
+        assert_eq!(current_name(&gallery).as_deref(), Some("a.png"));
+    }
+
+    #[test]
+    fn closing_the_viewer_forgets_the_current_image() {
+        let mut gallery = gallery_with(&["a.png", "b.png"]);
+
+        open(&mut gallery, "a.png");
+        let _ = gallery.update(Message::Close);
+        settle(&mut gallery);
+
+        assert!(!gallery.viewer.is_open(gallery.now));
+        assert_eq!(gallery.viewer.current(), None);
+    }
+
+    #[test]
+    fn missing_files_are_removed_and_the_viewer_moves_on() {
+        let mut gallery = gallery_with(&["a.png", "b.png", "c.png"]);
+
+        open(&mut gallery, "b.png");
+
+        let (_, id) = gallery.viewer.current().unwrap();
+        let missing = io::Error::from(io::ErrorKind::NotFound);
+
+        let _ = gallery
+            .update(Message::ImageDownloaded(id, Err(missing.into())));
+
+        assert_eq!(names(&gallery), ["a.png", "c.png"]);
+        assert_eq!(current_name(&gallery).as_deref(), Some("c.png"));
+    }
+
+    #[test]
+    fn the_difference_is_shown_until_another_image_is_opened() {
+        let mut gallery = gallery_with(&["a.png", "b.png", "c.png"]);
+        let ids: Vec<Id> =
+            gallery.images[..2].iter().map(|image| image.id).collect();
+
+        gallery.selected_ids.extend(ids);
+        let _ = gallery.update(Message::DiffSelected);
+        settle(&mut gallery);
+
+        assert!(gallery.diff.is_some());
+        assert_eq!(current_name(&gallery).as_deref(), Some("a.png"));
+
+        let _ = gallery.update(press(Named::ArrowRight));
+
+        assert!(gallery.diff.is_none());
+        assert_eq!(current_name(&gallery).as_deref(), Some("b.png"));
+    }
+
+    #[test]
+    fn formats_that_cannot_be_rotated_are_refused_up_front() {
+        let mut gallery = gallery_with(&["a.gif", "b.png"]);
+
+        open(&mut gallery, "b.png");
+        let _ = gallery.update(Message::RotateRight);
+        assert!(gallery.toast.is_none());
+
+        open(&mut gallery, "a.gif");
+        let _ = gallery.update(Message::RotateRight);
+        assert!(gallery.toast.is_some());
+    }
+
+    #[test]
+    fn thumbnails_of_the_old_size_are_dropped() {
+        let mut gallery = gallery_with(&["a.png", "b.png"]);
+        let id = gallery.images[0].id;
+
+        let _ = gallery.update(Message::ImagePoppedIn(id));
+        let _ = gallery.update(Message::ThumbnailSizeChanged(
+            ThumbnailSize::Large,
+        ));
+
+        assert_eq!(gallery.thumbnails_requested, 0);
+
+        let rgba = Rgba::from_image_buffer(image::RgbaImage::new(1, 1));
+        let _ = gallery.update(Message::ThumbnailDownloaded(id, 0, Ok(rgba)));
+
+        assert!(gallery.previews.get(&id).is_none());
+        assert_eq!(gallery.thumbnails_decoded, 0);
+    }
+
+    #[test]
+    fn arrow_keys_do_nothing_before_an_image_is_opened() {
+        let mut gallery = gallery_with(&["a.png", "b.png", "c.png"]);
+
+        for key in [Named::ArrowLeft, Named::ArrowRight] {
+            let task = gallery.update(press(key));
+
+            assert_eq!(task.units(), 0);
         }
-        .spacing(4)
-        .wrap();
 
-        let content = container(scrollable(center_x(gallery))
-            .spacing(4)
-            .on_scroll(Message::ViewportChanged))
-            .padding(4);
+        assert!(!gallery.viewer.is_open(gallery.now));
+        assert_eq!(gallery.viewer.current(), None);
+    }
+
+    #[test]
+    fn arrow_keys_do_nothing_once_the_viewer_is_closed() {
+        let mut gallery = gallery_with(&["a.png", "b.png", "c.png"]);
+
+        open(&mut gallery, "b.png");
+        let _ = gallery.update(Message::Close);
+        settle(&mut gallery);
 
-        let viewer = self.viewer.view(self.now);
+        for key in [
+            Named::ArrowLeft,
+            Named::ArrowRight,
+            Named::PageUp,
+            Named::PageDown,
+        ] {
+            let _ = gallery.update(press(key));
+        }
 
-        stack![content, viewer].into()
+        assert!(!gallery.viewer.is_open(gallery.now));
+        assert_eq!(gallery.viewer.current_index(), None);
+        assert_eq!(gallery.viewer.current_id(), None);
     }
-} 
\ No newline at end of file
+}